@@ -1,3 +1,7 @@
+use crate::consts::{
+    DEFAULT_ADAPTIVE_STEP_DECAY, DEFAULT_ADAPTIVE_STEP_GROWTH, DEFAULT_ADAPTIVE_STEP_RANGE, DEFAULT_BISECTION_MAX_FAILS,
+};
+use crate::optimizer::disrupt::{Disruption, DisruptionSet};
 use crate::optimizer::separator::SeparatorConfig;
 use crate::sample::search::SampleConfig;
 use jagua_rs::collision_detection::CDEConfig;
@@ -17,6 +21,7 @@ pub struct SparrowConfig {
     /// See [`jagua_rs::io::parser::Parser::new`] for more details.
     pub poly_simpl_tolerance: Option<f32>,
     /// Defines the minimum distance between items and other hazards.
+    /// Serves as the default clearance of items without a `clearance` of their own.
     /// Disabled if `None`.
     /// See [`jagua_rs::io::parser::Parser::new`] for more details.
     pub min_item_separation: Option<f32>,
@@ -34,6 +39,13 @@ pub struct ExplorationConfig {
     pub solution_pool_distribution_stddev: f32,
     pub separator_config: SeparatorConfig,
     pub large_item_ch_area_cutoff_percentile: f32,
+    /// Disruption operators applied to escape from an infeasible solution.
+    /// Every time, the operator is chosen by a UCB1 bandit which favours the operators that most often led to a feasible solution.
+    pub disruptions: DisruptionSet,
+    /// Schedule of the strip widths attempted in the exploration phase
+    pub width_schedule: WidthSchedule,
+    /// Explore with multiple separators in parallel which exchange their best solutions, disabled if `None`
+    pub islands: Option<IslandConfig>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -52,6 +64,75 @@ pub enum ShrinkDecayStrategy {
     FailureBased(f32),
 }
 
+/// Schedule of the strip widths attempted in the exploration phase
+#[derive(Debug, Clone, Copy)]
+pub enum WidthSchedule {
+    /// The strip shrinks by `shrink_step` after every feasible solution
+    Fixed,
+    /// The step starts at `shrink_step`, grows by `growth` after every feasible solution and decays by `decay` after
+    /// every failed attempt, staying within `[min_step, max_step]`
+    Adaptive {
+        growth: f32,
+        decay: f32,
+        min_step: f32,
+        max_step: f32,
+    },
+    /// Bisection between the best feasible width and a lower bound (the width at full density).
+    /// A width is given up after `max_fails` consecutive failed attempts, it then becomes the new lower bound.
+    /// Once both bounds are within `shrink_step` of each other, the strip shrinks by `shrink_step`.
+    Bisection { max_fails: usize },
+}
+
+/// Width schedules which can be selected from the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum WidthScheduleKind {
+    /// Shrink by a fixed step after every feasible solution
+    Fixed,
+    /// Grow the step after every feasible solution and decay it after every failed attempt
+    Adaptive,
+    /// Bisect between the best feasible width and a lower bound
+    Bisection,
+}
+
+impl WidthScheduleKind {
+    /// The schedule with its default parameters, relative to the shrink step of the exploration phase
+    pub fn schedule(self, shrink_step: f32) -> WidthSchedule {
+        match self {
+            WidthScheduleKind::Fixed => WidthSchedule::Fixed,
+            WidthScheduleKind::Adaptive => {
+                let (min_ratio, max_ratio) = DEFAULT_ADAPTIVE_STEP_RANGE;
+                WidthSchedule::Adaptive {
+                    growth: DEFAULT_ADAPTIVE_STEP_GROWTH,
+                    decay: DEFAULT_ADAPTIVE_STEP_DECAY,
+                    min_step: shrink_step * min_ratio,
+                    max_step: shrink_step * max_ratio,
+                }
+            }
+            WidthScheduleKind::Bisection => WidthSchedule::Bisection {
+                max_fails: DEFAULT_BISECTION_MAX_FAILS,
+            },
+        }
+    }
+}
+
+/// Configuration of the island model of the exploration phase
+#[derive(Debug, Clone, Copy)]
+pub struct IslandConfig {
+    /// Number of separators exploring in parallel, each with its own seed and a variation of the configuration
+    pub n_islands: usize,
+    /// Number of epochs the exploration time is split in, after every epoch the best feasible solution migrates to all islands
+    pub n_epochs: usize,
+}
+
+/// Objective of the open dimension mode, in which both the width and height of the container are variable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OpenDimObjective {
+    /// Minimize the area of the container
+    Area,
+    /// Minimize the perimeter of the container
+    Perimeter,
+}
+
 pub const DEFAULT_SPARROW_CONFIG: SparrowConfig = SparrowConfig {
     rng_seed: None,
     expl_cfg: ExplorationConfig {
@@ -71,6 +152,9 @@ pub const DEFAULT_SPARROW_CONFIG: SparrowConfig = SparrowConfig {
             },
         },
         large_item_ch_area_cutoff_percentile: 0.75,
+        disruptions: DisruptionSet::single(Disruption::SwapLarge),
+        width_schedule: WidthSchedule::Fixed,
+        islands: None,
     },
     cmpr_cfg: CompressionConfig {
         shrink_range: (0.0005, 0.00001),
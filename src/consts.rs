@@ -33,8 +33,56 @@ pub const DEFAULT_COMPRESS_TIME_RATIO: f32 = 0.2;
 
 pub const DEFAULT_MAX_CONSEQ_FAILS_EXPL: usize = 10;
 
+/// Parameters of the adaptive width schedule when selected from the command line: growth and decay of the step,
+/// and the range of the step relative to the default shrink step
+pub const DEFAULT_ADAPTIVE_STEP_GROWTH: f32 = 1.5;
+pub const DEFAULT_ADAPTIVE_STEP_DECAY: f32 = 0.8;
+pub const DEFAULT_ADAPTIVE_STEP_RANGE: (f32, f32) = (0.1, 10.0);
+
+/// Consecutive failed attempts after which the bisection width schedule gives up on a width
+pub const DEFAULT_BISECTION_MAX_FAILS: usize = 3;
+
+/// Number of epochs of the island model, after every epoch the best solution migrates to all islands
+pub const DEFAULT_N_ISLAND_EPOCHS: usize = 10;
+
+/// Multipliers of the shrink step and the separator's iterations without improvement, cycled over the islands
+pub const ISLAND_CONFIG_VARIATIONS: [(f32, f32); 4] = [(1.0, 1.0), (2.0, 0.5), (0.5, 2.0), (1.0, 0.5)];
+
 pub const DEFAULT_FAIL_DECAY_RATIO_CMPR: f32 = 0.9;
 
+/// Maximum number of items removed from the knapsack to make room for an item to insert
+pub const KNAPSACK_MAX_REMOVALS: usize = 2;
+
+/// Ratio by which the shrink step decays when the container could not be shrunk along either axis (open dimension mode)
+pub const OPEN_DIM_STEP_DECAY: f32 = 0.5;
+
+/// Number of rounds in which the exploration time is shared over the rolls of a catalogue
+pub const ROLL_SELECTION_ROUNDS: usize = 3;
+
+/// Minimum width (and height) of the strip between its border margins, relative to the margin
+pub const MIN_STRIP_WIDTH_MARGIN_RATIO: f32 = 0.01;
+
+/// Relative tolerance when verifying the clearances of a solution, to account for rounding errors
+pub const CLEARANCE_VERIFICATION_TOLERANCE: f32 = 1e-3;
+
+/// Weight of the penalty for items of the same proximity group which are too far apart, relative to the collision loss
+pub const PROXIMITY_PENALTY_WEIGHT: f32 = 0.1;
+
+/// Weight of the penalty for disfavored orientations of an item in the separator, relative to its area
+pub const ORIENTATION_PENALTY_WEIGHT: f32 = 0.1;
+
+/// Number of items moved to a random position by the relocation disruption
+pub const DSRP_N_RELOCATED_ITEMS: usize = 3;
+
+/// Width of the band of the strip ruined by the ruin-and-recreate disruption, relative to the strip width
+pub const DSRP_RUIN_WIDTH_RATIO: f32 = 0.1;
+
+/// Weight of the exploration bonus in the adaptive selection of disruption operators (UCB1)
+pub const DSRP_UCB_EXPLORATION_FACTOR: f32 = 0.5;
+
+/// A rotation matches an orientation with a cost if it deviates less than this (in radians)
+pub const ORIENTATION_MATCH_TOLERANCE: f32 = f32::to_radians(0.5);
+
 pub const LOG_LEVEL_FILTER_RELEASE: log::LevelFilter = log::LevelFilter::Info;
 
 pub const LOG_LEVEL_FILTER_DEBUG: log::LevelFilter = log::LevelFilter::Debug;
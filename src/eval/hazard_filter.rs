@@ -0,0 +1,21 @@
+use jagua_rs::collision_detection::hazards::HazardEntity;
+use jagua_rs::collision_detection::hazards::filter::{HazardFilter, QZHazardFilter};
+
+/// Whether a collision with the hazard has to be resolved for an item with the given quality zone filter.
+/// Quality zones only restrict items which require a higher quality than the zone provides.
+#[inline(always)]
+pub fn hazard_is_relevant(entity: &HazardEntity, qz_filter: Option<&QZHazardFilter>) -> bool {
+    match entity {
+        HazardEntity::InferiorQualityZone { .. } => qz_filter.is_some_and(|f| !f.is_irrelevant(entity)),
+        _ => true,
+    }
+}
+
+/// [`HazardFilter`] which ignores all hazards that do not restrict the item, see [`hazard_is_relevant`].
+pub struct ItemHazardFilter<'a>(pub Option<&'a QZHazardFilter>);
+
+impl HazardFilter for ItemHazardFilter<'_> {
+    fn is_irrelevant(&self, entity: &HazardEntity) -> bool {
+        !hazard_is_relevant(entity, self.0)
+    }
+}
@@ -1,10 +1,15 @@
-use jagua_rs::collision_detection::hazards::filter::NoFilter;
+use crate::eval::hazard_filter::{ItemHazardFilter, hazard_is_relevant};
 use crate::eval::sample_eval::{SampleEval, SampleEvaluator};
+use crate::quantify::exceeds_bbox;
+use crate::quantify::nesting::{holes_of, shapes_are_nested};
+use crate::quantify::orientation::orientation_cost;
+use jagua_rs::collision_detection::hazards::HazardEntity;
+use jagua_rs::collision_detection::hazards::collector::{BasicHazardCollector, HazardCollector};
 use jagua_rs::entities::Item;
 use jagua_rs::entities::Layout;
 use jagua_rs::geometry::DTransformation;
 use jagua_rs::geometry::geo_traits::TransformableFrom;
-use jagua_rs::geometry::primitives::SPolygon;
+use jagua_rs::geometry::primitives::{Rect, SPolygon};
 
 pub const X_MULTIPLIER: f32 = 10.0;
 pub const Y_MULTIPLIER: f32 = 1.0;
@@ -16,6 +21,11 @@ pub struct LBFEvaluator<'a> {
     layout: &'a Layout,
     item: &'a Item,
     shape_buff: SPolygon,
+    item_holes: &'a [Vec<SPolygon>],
+    /// Whether the item can be nested in a placed item or nest one itself
+    nesting: bool,
+    region: Option<Rect>,
+    orientation_costs: &'a [(f32, f32)],
     n_evals: usize
 }
 
@@ -25,9 +35,59 @@ impl<'a> LBFEvaluator<'a> {
             layout,
             item,
             shape_buff: item.shape_cd.as_ref().clone(),
+            item_holes: &[],
+            nesting: false,
+            region: None,
+            orientation_costs: &[],
             n_evals: 0
         }
     }
+
+    /// Allows the item to be nested in the holes of placed items (and vice versa)
+    pub fn with_item_holes(mut self, item_holes: &'a [Vec<SPolygon>]) -> Self {
+        self.item_holes = item_holes;
+        //only possible if the item has holes itself, or a placed item has a hole large enough to hold it
+        self.nesting = !holes_of(item_holes, self.item.id).is_empty()
+            || self.layout.placed_items.values().any(|pi| {
+                holes_of(item_holes, pi.item_id)
+                    .iter()
+                    .any(|hole| hole.area >= self.item.shape_cd.area)
+            });
+        self
+    }
+
+    /// Rejects all placements outside of the region
+    pub fn with_region(mut self, region: Option<Rect>) -> Self {
+        self.region = region;
+        self
+    }
+
+    /// Penalizes the disfavored orientations of the item:
+    /// an orientation with cost 1 is worth shifting the item by half its diameter along the strip
+    pub fn with_orientation_costs(mut self, orientation_costs: &'a [(f32, f32)]) -> Self {
+        self.orientation_costs = orientation_costs;
+        self
+    }
+
+    /// Whether all collisions of the (transformed) shape buffer are with items it is nested with
+    fn all_collisions_nested(&self, dt: DTransformation) -> bool {
+        let mut collector = BasicHazardCollector::new();
+        self.layout.cde().collect_poly_collisions(&self.shape_buff, &mut collector);
+        collector.iter().all(|(_, he)| match he {
+            HazardEntity::PlacedItem { pk, .. } => {
+                let other = &self.layout.placed_items[*pk];
+                shapes_are_nested(
+                    &self.shape_buff,
+                    holes_of(self.item_holes, self.item.id),
+                    dt,
+                    &other.shape,
+                    holes_of(self.item_holes, other.item_id),
+                    other.d_transf,
+                )
+            }
+            _ => !hazard_is_relevant(he, self.item.hazard_filter.as_ref()),
+        })
+    }
 }
 
 impl<'a> SampleEvaluator for LBFEvaluator<'a> {
@@ -35,22 +95,24 @@ impl<'a> SampleEvaluator for LBFEvaluator<'a> {
         self.n_evals += 1;
         let cde = self.layout.cde();
         let transf = dt.into();
-        match cde.detect_surrogate_collision(self.item.shape_cd.surrogate(), &transf, &NoFilter) {
-            true => SampleEval::Invalid, // Surrogate collides with something
-            false => {
-                self.shape_buff.transform_from(&self.item.shape_cd, &transf);
-                match cde.detect_poly_collision(&self.shape_buff, &NoFilter) {
-                    true => SampleEval::Invalid, // Exact shape collides with something
-                    false => {
-                        // No collisions
-                        let poi = self.shape_buff.poi.center;
-                        let bbox_corner = self.shape_buff.bbox.corners()[0];
-                        let loss = X_MULTIPLIER * (poi.0 + bbox_corner.0) + Y_MULTIPLIER * (poi.1 + bbox_corner.1);
-                        SampleEval::Clear{loss}
-                    }
-                }
-            }
+        let filter = ItemHazardFilter(self.item.hazard_filter.as_ref());
+        //the surrogate cannot be used to reject samples when the item can be nested
+        if !self.nesting && cde.detect_surrogate_collision(self.item.shape_cd.surrogate(), &transf, &filter) {
+            return SampleEval::Invalid; // Surrogate collides with something
+        }
+        self.shape_buff.transform_from(&self.item.shape_cd, &transf);
+        if self.region.is_some_and(|r| exceeds_bbox(&self.shape_buff, r)) {
+            return SampleEval::Invalid; // Outside of the item's region
+        }
+        if cde.detect_poly_collision(&self.shape_buff, &filter) && !(self.nesting && self.all_collisions_nested(dt)) {
+            return SampleEval::Invalid; // Exact shape collides with something
         }
+        // No collisions
+        let poi = self.shape_buff.poi.center;
+        let bbox_corner = self.shape_buff.bbox.corners()[0];
+        let orientation_loss = orientation_cost(self.orientation_costs, dt.rotation()) * self.item.shape_cd.diameter;
+        let loss = X_MULTIPLIER * (poi.0 + bbox_corner.0 + orientation_loss) + Y_MULTIPLIER * (poi.1 + bbox_corner.1);
+        SampleEval::Clear{loss}
     }
 
     fn n_evals(&self) -> usize {
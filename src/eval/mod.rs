@@ -1,3 +1,4 @@
+pub mod hazard_filter;
 pub mod sample_eval;
 pub mod lbf_evaluator;
 pub mod sep_evaluator;
@@ -1,6 +1,8 @@
 use jagua_rs::collision_detection::hazards::collector::HazardCollector;
 use crate::eval::sample_eval::{SampleEval, SampleEvaluator};
 use crate::eval::specialized_jaguars_pipeline::{collect_poly_collisions_in_detector_custom, SpecializedHazardCollector};
+use crate::quantify::orientation::{orientation_cost, quantify_orientation_penalty};
+use crate::quantify::proximity::{ProximityGroups, quantify_proximity_violation};
 use crate::quantify::tracker::CollisionTracker;
 use jagua_rs::entities::Item;
 use jagua_rs::entities::Layout;
@@ -11,8 +13,12 @@ use jagua_rs::geometry::primitives::SPolygon;
 pub struct SeparationEvaluator<'a> {
     layout: &'a Layout,
     item: &'a Item,
+    current_pk: PItemKey,
     collector: SpecializedHazardCollector<'a>,
     shape_buff: SPolygon,
+    /// Placed items of the same proximity group as the item, along with their maximum x-distance
+    group_members: Vec<(PItemKey, f32)>,
+    orientation_costs: &'a [(f32, f32)],
     n_evals: usize,
 }
 
@@ -23,16 +29,50 @@ impl<'a> SeparationEvaluator<'a> {
         current_pk: PItemKey,
         ct: &'a CollisionTracker,
     ) -> Self {
-        let collector = SpecializedHazardCollector::new(layout, ct, current_pk);
+        let collector = SpecializedHazardCollector::new(layout, ct, current_pk, item.id);
 
         Self {
             layout,
             item,
+            current_pk,
             collector,
             shape_buff: item.shape_cd.as_ref().clone(),
+            group_members: vec![],
+            orientation_costs: &[],
             n_evals: 0,
         }
     }
+
+    /// Penalizes the distance to the members of the item's proximity group which are too far away
+    pub fn with_proximity_groups(mut self, proximity: &ProximityGroups) -> Self {
+        self.group_members = match proximity.is_empty() {
+            true => vec![],
+            false => self
+                .layout
+                .placed_items
+                .iter()
+                .filter(|(pk, _)| *pk != self.current_pk)
+                .filter_map(|(pk, pi)| proximity.max_dist(self.item.id, pi.item_id).map(|d| (pk, d)))
+                .collect(),
+        };
+        self
+    }
+
+    /// Penalizes the disfavored orientations of the item
+    pub fn with_orientation_costs(mut self, orientation_costs: &'a [(f32, f32)]) -> Self {
+        self.orientation_costs = orientation_costs;
+        self
+    }
+
+    /// Penalty for the members of the item's proximity group which are too far from the shape buffer
+    fn proximity_loss(&self) -> f32 {
+        self.group_members
+            .iter()
+            .map(|(pk, max_dist)| {
+                quantify_proximity_violation(&self.shape_buff, &self.layout.placed_items[*pk].shape, *max_dist)
+            })
+            .sum()
+    }
 }
 
 impl<'a> SampleEvaluator for SeparationEvaluator<'a> {
@@ -58,7 +98,12 @@ impl<'a> SampleEvaluator for SeparationEvaluator<'a> {
             //but its loss was above the loss bound anyway
             SampleEval::Invalid
         } else if self.collector.is_empty() {
-            SampleEval::Clear { loss: 0.0 }
+            //the proximity and orientation penalties are soft: they only rank the collision-free positions
+            let orientation_loss = match self.orientation_costs.is_empty() {
+                true => 0.0,
+                false => quantify_orientation_penalty(self.item, orientation_cost(self.orientation_costs, dt.rotation())),
+            };
+            SampleEval::Clear { loss: self.proximity_loss() + orientation_loss }
         } else {
             SampleEval::Collision {
                 loss: self.collector.loss(&self.shape_buff),
@@ -1,24 +1,28 @@
 use std::f32::consts::PI;
-use crate::quantify::quantify_collision_poly_container;
+use crate::quantify::{exceeds_bbox, quantify_collision_poly_container, quantify_collision_poly_hole};
 #[cfg(not(feature = "simd"))]
 use crate::quantify::quantify_collision_poly_poly;
 #[cfg(feature = "simd")]
 use crate::quantify::simd::circles_soa::CirclesSoA;
 #[cfg(feature = "simd")]
 use crate::quantify::simd::quantify_collision_poly_poly_simd;
+use crate::quantify::nesting::{holes_of, shapes_are_nested};
 use crate::quantify::tracker::CollisionTracker;
 use crate::util::assertions;
+use crate::eval::hazard_filter::hazard_is_relevant;
 use crate::util::bit_reversal_iterator::BitReversalIterator;
 use float_cmp::approx_eq;
 use jagua_rs::collision_detection::CDEngine;
 use jagua_rs::collision_detection::hazards::collector::HazardCollector;
+use jagua_rs::collision_detection::hazards::filter::QZHazardFilter;
 use jagua_rs::collision_detection::hazards::{HazKey, HazardEntity};
 use jagua_rs::collision_detection::quadtree::QTHazPresence;
 use jagua_rs::entities::Layout;
 use jagua_rs::entities::PItemKey;
 use jagua_rs::geometry::DTransformation;
 use jagua_rs::geometry::geo_traits::{TransformableFrom};
-use jagua_rs::geometry::primitives::SPolygon;
+use jagua_rs::geometry::primitives::{Rect, SPolygon};
+use itertools::Itertools;
 use slotmap::SecondaryMap;
 
 /// Functionally identical to [`CDEngine::collect_poly_collisions`], but with early return.
@@ -32,11 +36,16 @@ pub fn collect_poly_collisions_in_detector_custom(
     collector: &mut SpecializedHazardCollector,
 ) {
     let t = dt.compose();
+    collector.current_dt = *dt;
     // transform the shape buffer to the new position
     let shape = shape_buffer.transform_from(reference_shape, &t);
 
     #[cfg(feature = "simd")]
     collector.poles_soa.load(&shape.surrogate().poles);
+
+    // Leaving the placement region is detected on the bounding box alone, which is cheap
+    collector.collect_region_violation(shape);
+    if collector.early_terminate(shape) { return; }
     
     // Start off by checking a few poles to detect obvious collisions quickly
     {
@@ -83,6 +92,9 @@ pub fn collect_poly_collisions_in_detector_custom(
         }
     }
     
+    // Items nested in each other's holes overlap, but do not collide
+    collector.remove_nested(shape);
+
     // At this point, all collisions should be present in the detector.
     debug_assert!(assertions::custom_pipeline_matches_jaguars(shape, collector), "Custom pipeline deviates from native jagua-rs pipeline");
 }
@@ -95,6 +107,13 @@ pub struct SpecializedHazardCollector<'a> {
     pub ct: &'a CollisionTracker,
     pub current_pk: PItemKey,
     pub current_haz_key: HazKey,
+    /// Item (variant) and transformation of the shape being evaluated, to place its holes
+    pub current_item_id: usize,
+    pub current_dt: DTransformation,
+    /// Quality zone filter of the current item, zones it is allowed to be placed in are not collected
+    pub qz_filter: Option<&'a QZHazardFilter>,
+    /// Key of the exterior hazard and the part of the container the current item is allowed in, if it has a region
+    pub region: Option<(HazKey, Rect)>,
     pub detected: SecondaryMap<HazKey, (HazardEntity, usize)>,
     pub idx_counter: usize,
     pub loss_cache: (usize, f32),
@@ -108,13 +127,26 @@ impl<'a> SpecializedHazardCollector<'a> {
         layout: &'a Layout,
         ct: &'a CollisionTracker,
         current_pk: PItemKey,
+        current_item_id: usize,
     ) -> Self {
         let current_haz_key = layout.cde().haz_key_from_pi_key(current_pk).expect("placed item should be registered in the CDE");
+        let qz_filter = layout.placed_items[current_pk].hazard_filter.as_ref();
+        let region = ct.region(current_item_id).map(|_| {
+            let ext_hkey = layout.cde().hazards_map.iter()
+                .find(|(_, h)| matches!(h.entity, HazardEntity::Exterior))
+                .map(|(hkey, _)| hkey)
+                .expect("exterior should be registered in the CDE");
+            (ext_hkey, ct.allowed_bbox(layout, current_item_id))
+        });
         Self {
             layout,
             ct,
             current_pk,
             current_haz_key,
+            current_item_id,
+            current_dt: layout.placed_items[current_pk].d_transf,
+            qz_filter,
+            region,
             detected: SecondaryMap::with_capacity(layout.placed_items.len() + 1),
             idx_counter: 0,
             loss_cache: (0, 0.0),
@@ -143,18 +175,62 @@ impl<'a> SpecializedHazardCollector<'a> {
         let (cache_idx, cached_loss) = self.loss_cache;
         if cache_idx < self.idx_counter {
             // additional hazards were detected, update the cache
-            let extra_loss: f32 = self.iter_with_index()
-                .filter(|(_, idx)| *idx >= cache_idx)
-                .map(|(h, _)| self.calc_weighted_loss(h, shape))
+            let extra_loss: f32 = self.detected.iter()
+                .filter(|(_, (_, idx))| *idx >= cache_idx)
+                .map(|(hkey, (h, _))| self.calc_weighted_loss(hkey, h, shape))
                 .sum();
             self.loss_cache = (self.idx_counter, cached_loss + extra_loss);
         }
-        debug_assert!(approx_eq!(f32, self.loss_cache.1, self.iter().map(|(_, he)| self.calc_weighted_loss(he, shape)).sum()));
+        debug_assert!(approx_eq!(f32, self.loss_cache.1, self.iter().map(|(hkey, he)| self.calc_weighted_loss(hkey, he, shape)).sum()));
         self.loss_cache.1
     }
 
-    fn calc_weighted_loss(&self, haz: &HazardEntity, shape: &SPolygon) -> f32 {
+    /// Registers a collision with the exterior if the shape leaves the placement region of the current item
+    pub fn collect_region_violation(&mut self, shape: &SPolygon) {
+        if let Some((ext_hkey, r)) = self.region {
+            if !self.contains_key(ext_hkey) && exceeds_bbox(shape, r) {
+                self.insert(ext_hkey, HazardEntity::Exterior);
+            }
+        }
+    }
+
+    /// Whether the shape (of the current item) and the other placed item are nested in each other's holes
+    pub fn is_nested(&self, other_pk: PItemKey, shape: &SPolygon) -> bool {
+        if self.ct.item_holes.is_empty() {
+            return false;
+        }
+        let other = &self.layout.placed_items[other_pk];
+        shapes_are_nested(
+            shape,
+            holes_of(&self.ct.item_holes, self.current_item_id),
+            self.current_dt,
+            &other.shape,
+            holes_of(&self.ct.item_holes, other.item_id),
+            other.d_transf,
+        )
+    }
+
+    /// Removes all detected items which are nested with the shape
+    pub fn remove_nested(&mut self, shape: &SPolygon) {
+        if self.ct.item_holes.is_empty() {
+            return;
+        }
+        let nested = self
+            .detected
+            .iter()
+            .filter_map(|(hkey, (haz, _))| match haz {
+                HazardEntity::PlacedItem { pk, .. } if self.is_nested(*pk, shape) => Some(hkey),
+                _ => None,
+            })
+            .collect_vec();
+        for hkey in nested {
+            self.remove_by_key(hkey);
+        }
+    }
+
+    fn calc_weighted_loss(&self, hkey: HazKey, haz: &HazardEntity, shape: &SPolygon) -> f32 {
         match haz {
+            HazardEntity::PlacedItem { pk: other_pk, .. } if self.is_nested(*other_pk, shape) => 0.0,
             HazardEntity::PlacedItem { pk: other_pk, .. } => {
                 let other_shape = &self.layout.placed_items[*other_pk].shape;
 
@@ -167,10 +243,24 @@ impl<'a> SpecializedHazardCollector<'a> {
                 loss * weight
             }
             HazardEntity::Exterior => {
-                let loss = quantify_collision_poly_container(shape, self.layout.container.outer_cd.bbox);
+                let c_bbox = self.region.map_or(self.layout.container.outer_cd.bbox, |(_, r)| r);
+                let loss = quantify_collision_poly_container(shape, c_bbox);
                 let weight = self.ct.get_container_weight(self.current_pk);
                 loss * weight
             }
+            HazardEntity::Hole { .. } => {
+                let hole_shape = &self.layout.cde().hazards_map[hkey].shape;
+                let loss = quantify_collision_poly_hole(shape, hole_shape);
+                let weight = self.ct.get_hole_weight(self.current_pk);
+                loss * weight
+            }
+            HazardEntity::InferiorQualityZone { .. } => {
+                //only relevant zones are ever inserted in the collector
+                let zone_shape = &self.layout.cde().hazards_map[hkey].shape;
+                let loss = quantify_collision_poly_hole(shape, zone_shape);
+                let weight = self.ct.get_hole_weight(self.current_pk);
+                loss * weight
+            }
             _ => unimplemented!("unsupported hazard entity"),
         }
     }
@@ -182,6 +272,10 @@ impl<'a> HazardCollector for SpecializedHazardCollector<'a> {
     }
 
     fn insert(&mut self, hkey: HazKey, entity: HazardEntity) {
+        if !hazard_is_relevant(&entity, self.qz_filter) {
+            //the item is allowed to be placed in this quality zone
+            return;
+        }
         debug_assert!(!self.contains_key(hkey));
         self.detected.insert(hkey, (entity, self.idx_counter));
         self.idx_counter += 1;
@@ -6,20 +6,24 @@ use rand::SeedableRng;
 use rand::prelude::{Rng, SmallRng};
 use rand_chacha::ChaCha20Rng;
 use sparrow::config::*;
-use sparrow::optimizer::optimize;
+use sparrow::optimizer::modes::{
+    solve_bin_packing, solve_knapsack, solve_marker_planning, solve_open_dimension, solve_roll_selection,
+    solve_strip_packing,
+};
 use sparrow::util::io;
-use sparrow::util::io::{MainCli, SPOutput};
+use sparrow::util::io::{ExtItemFeatures, ExtStripFeatures, MainCli, Mode};
 use std::fs;
 use std::path::Path;
 use std::time::Duration;
 
-use jagua_rs::io::import::Importer;
-use sparrow::EPOCH;
+use jagua_rs::io::svg::s_layout_to_svg;
+use jagua_rs::probs::spp::io::ext_repr::ExtSPInstance;
+use serde::Serialize;
 
 use anyhow::{Result, bail};
 use sparrow::consts::{
     DEFAULT_COMPRESS_TIME_RATIO, DEFAULT_EXPLORE_TIME_RATIO, DEFAULT_FAIL_DECAY_RATIO_CMPR,
-    DEFAULT_MAX_CONSEQ_FAILS_EXPL, LOG_LEVEL_FILTER_DEBUG, LOG_LEVEL_FILTER_RELEASE,
+    DEFAULT_MAX_CONSEQ_FAILS_EXPL, DEFAULT_N_ISLAND_EPOCHS, DRAW_OPTIONS, LOG_LEVEL_FILTER_DEBUG, LOG_LEVEL_FILTER_RELEASE,
 };
 use sparrow::util::ctrlc_terminator::CtrlCTerminator;
 use sparrow::util::svg_exporter::SvgExporter;
@@ -57,6 +61,16 @@ fn main() -> Result<()> {
     };
     config.expl_cfg.time_limit = explore_dur;
     config.cmpr_cfg.time_limit = compress_dur;
+    if !args.disruptions.is_empty() {
+        config.expl_cfg.disruptions = args.disruptions.iter().copied().collect();
+    }
+    config.expl_cfg.width_schedule = args.width_schedule.schedule(config.expl_cfg.shrink_step);
+    if let Some(n_islands) = args.islands {
+        config.expl_cfg.islands = Some(IslandConfig {
+            n_islands: n_islands as usize,
+            n_epochs: DEFAULT_N_ISLAND_EPOCHS,
+        });
+    }
     if args.early_termination {
         config.expl_cfg.max_conseq_failed_attempts = Some(DEFAULT_MAX_CONSEQ_FAILS_EXPL);
         config.cmpr_cfg.shrink_decay =
@@ -90,55 +104,145 @@ fn main() -> Result<()> {
 
     info!("[MAIN] system time: {}", jiff::Timestamp::now());
 
-    let ext_instance = io::read_spp_instance_json(Path::new(&input_file_path))?;
+    if args.init_solution.is_some() && args.mode != Mode::Strip {
+        bail!("an initial solution is only supported in strip packing mode");
+    }
 
-    let importer = Importer::new(
-        config.cde_config,
-        config.poly_simpl_tolerance,
-        config.min_item_separation,
-        config.narrow_concavity_cutoff_ratio,
-    );
-    let instance = jagua_rs::probs::spp::io::import(&importer, &ext_instance)?;
+    let n_runs = args.runs as usize;
+    if n_runs > 1 && !matches!(args.mode, Mode::Strip | Mode::FoldedStrip) {
+        bail!("multiple runs are only supported in (folded) strip packing mode");
+    }
 
-    info!(
-        "[MAIN] loaded instance {} with #{} items",
-        ext_instance.name,
-        instance.total_item_qty()
-    );
+    match args.mode {
+        Mode::Strip | Mode::FoldedStrip => {
+            let (ext_instance, ext_item_features, ext_strip_features) = read_spp_inputs(input_file_path)?;
+            let init_output = match &args.init_solution {
+                Some(path) => {
+                    info!("[MAIN] warm starting from solution {}", path);
+                    Some(io::read_spp_output_json(Path::new(path))?)
+                }
+                None => None,
+            };
+            let name = ext_instance.name.clone();
+            let output = solve_strip_packing(
+                ext_instance,
+                ext_item_features,
+                ext_strip_features,
+                init_output.as_ref(),
+                args.mode == Mode::FoldedStrip,
+                n_runs,
+                &config,
+                rng,
+                &mut create_svg_exporter(&name),
+                &mut CtrlCTerminator::new(),
+            )?;
+            write_final_output(&output, &name)
+        }
+        Mode::OpenDimension => {
+            let (ext_instance, ext_item_features, ext_strip_features) = read_spp_inputs(input_file_path)?;
+            let name = ext_instance.name.clone();
+            let output = solve_open_dimension(
+                ext_instance,
+                ext_item_features,
+                ext_strip_features,
+                args.objective,
+                &config,
+                rng,
+                &mut create_svg_exporter(&name),
+                &mut CtrlCTerminator::new(),
+            )?;
+            write_final_output(&output, &name)
+        }
+        Mode::RollSelection => {
+            let (ext_instance, ext_item_features, ext_strip_features) = read_spp_inputs(input_file_path)?;
+            let ext_catalogue = io::read_roll_catalogue_json(Path::new(&input_file_path))?;
+            let name = ext_instance.name.clone();
+            let output = solve_roll_selection(
+                ext_instance,
+                ext_item_features,
+                ext_strip_features,
+                &ext_catalogue,
+                &config,
+                rng,
+                &mut create_svg_exporter(&name),
+                &mut CtrlCTerminator::new(),
+            )?;
+            write_final_output(&output, &name)
+        }
+        Mode::MarkerPlanning => {
+            let (ext_instance, ext_item_features, ext_strip_features) = read_spp_inputs(input_file_path)?;
+            let ext_order = io::read_marker_order_json(Path::new(&input_file_path))?;
+            let name = ext_instance.name.clone();
+            let output = solve_marker_planning(
+                ext_instance,
+                ext_item_features,
+                ext_strip_features,
+                &ext_order,
+                &config,
+                rng,
+                |i| create_svg_exporter(&format!("{}_marker_{}", name, i)),
+                &mut CtrlCTerminator::new(),
+            )?;
+            write_final_output(&output, &name)
+        }
+        Mode::Knapsack => {
+            let ext_instance = io::read_kp_instance_json(Path::new(&input_file_path))?;
+            let ext_strip_features = io::read_strip_features_json(Path::new(&input_file_path))?;
+            let ext_item_features = io::read_item_features_json(Path::new(&input_file_path))?;
+            let name = ext_instance.name.clone();
+            let output = solve_knapsack(
+                ext_instance,
+                ext_item_features,
+                ext_strip_features,
+                &config,
+                rng,
+                &mut create_svg_exporter(&name),
+                &mut CtrlCTerminator::new(),
+            )?;
+            write_final_output(&output, &name)
+        }
+        Mode::BinPacking => {
+            let ext_instance = io::read_bpp_instance_json(Path::new(&input_file_path))?;
+            let name = ext_instance.name.clone();
+            let (instance, solution, output) =
+                solve_bin_packing(ext_instance, &config, rng, &mut CtrlCTerminator::new())?;
+
+            //export every sheet as a separate svg
+            for (i, layout_snapshot) in solution.layout_snapshots.values().enumerate() {
+                let title = format!("final_{}_sheet_{}", name, i);
+                let svg = s_layout_to_svg(layout_snapshot, &instance, DRAW_OPTIONS, title.as_str());
+                io::write_svg(&svg, Path::new(&format!("{OUTPUT_DIR}/{title}.svg")), Level::Info)?;
+            }
+            write_final_output(&output, &name)
+        }
+    }
+}
 
-    let mut svg_exporter = {
-        let final_svg_path = Some(format!("{OUTPUT_DIR}/final_{}.svg", ext_instance.name));
+/// Reads a strip packing instance along with the (optional) features of its items and of its strip
+fn read_spp_inputs(input_file_path: &str) -> Result<(ExtSPInstance, ExtItemFeatures, ExtStripFeatures)> {
+    let ext_instance = io::read_spp_instance_json(Path::new(&input_file_path))?;
+    let ext_item_features = io::read_item_features_json(Path::new(&input_file_path))?;
+    let ext_strip_features = io::read_strip_features_json(Path::new(&input_file_path))?;
+    Ok((ext_instance, ext_item_features, ext_strip_features))
+}
 
-        let intermediate_svg_dir = match cfg!(feature = "only_final_svg") {
-            true => None,
-            false => Some(format!("{OUTPUT_DIR}/sols_{}", ext_instance.name)),
-        };
+fn write_final_output(output: &impl Serialize, instance_name: &str) -> Result<()> {
+    let json_path = format!("{OUTPUT_DIR}/final_{instance_name}.json");
+    io::write_json(output, Path::new(&json_path), Level::Info)
+}
 
-        let live_svg_path = match cfg!(feature = "live_svg") {
-            true => Some(format!("{LIVE_DIR}/.live_solution.svg")),
-            false => None,
-        };
+fn create_svg_exporter(instance_name: &str) -> SvgExporter {
+    let final_svg_path = Some(format!("{OUTPUT_DIR}/final_{instance_name}.svg"));
 
-        SvgExporter::new(final_svg_path, intermediate_svg_dir, live_svg_path)
+    let intermediate_svg_dir = match cfg!(feature = "only_final_svg") {
+        true => None,
+        false => Some(format!("{OUTPUT_DIR}/sols_{instance_name}")),
     };
 
-    let mut ctrlc_terminator = CtrlCTerminator::new();
-
-    let solution = optimize(
-        instance.clone(),
-        rng,
-        &mut svg_exporter,
-        &mut ctrlc_terminator,
-        &config.expl_cfg,
-        &config.cmpr_cfg,
-    );
-
-    let json_path = format!("{OUTPUT_DIR}/final_{}.json", ext_instance.name);
-    let json_output = SPOutput {
-        instance: ext_instance,
-        solution: jagua_rs::probs::spp::io::export(&instance, &solution, *EPOCH),
+    let live_svg_path = match cfg!(feature = "live_svg") {
+        true => Some(format!("{LIVE_DIR}/.live_solution.svg")),
+        false => None,
     };
-    io::write_json(&json_output, Path::new(json_path.as_str()), Level::Info)?;
 
-    Ok(())
+    SvgExporter::new(final_svg_path, intermediate_svg_dir, live_svg_path)
 }
@@ -0,0 +1,116 @@
+use crate::optimizer::bpp::{BinSeparator, bpp_cost, separate_bin};
+use crate::util::terminator::Terminator;
+use itertools::Itertools;
+use jagua_rs::entities::Instance;
+use jagua_rs::probs::bpp::entities::{BPInstance, BPLayoutType, BPPlacement, BPProblem, BPSolution, LayKey};
+use log::info;
+use rand::Rng;
+use rand::prelude::IteratorRandom;
+
+/// Bin packing analogue of the compression phase.
+/// Tries to transfer the contents of sheets to cheaper sheet types, separating them with the given separator.
+/// Returns the best feasible solution found.
+pub fn downsize_phase(
+    instance: &BPInstance,
+    prob: &mut BPProblem,
+    sep: &mut BinSeparator,
+    rng: &mut impl Rng,
+    term: &impl Terminator,
+) -> BPSolution {
+    let init_cost = bpp_cost(prob);
+    let mut best = prob.save();
+    //(bin id of the sheet, bin id of the cheaper sheet) combinations which were already tried unsuccessfully
+    let mut failed_attempts: Vec<(usize, usize)> = vec![];
+
+    while !term.kill() {
+        //collect all sheets which could be transferred to a cheaper sheet type with enough area to hold its items
+        let candidate = prob
+            .layouts
+            .iter()
+            .flat_map(|(lkey, l)| {
+                let current_bin = &instance.bins[l.container.id];
+                let item_area = l
+                    .placed_items
+                    .values()
+                    .map(|pi| instance.item(pi.item_id).shape_cd.area)
+                    .sum::<f32>();
+                instance
+                    .bins
+                    .iter()
+                    .filter(move |b| b.cost < current_bin.cost)
+                    .filter(move |b| b.container.outer_cd.area >= item_area)
+                    .map(move |b| (lkey, current_bin.id, b.id))
+            })
+            .filter(|(_, from, to)| prob.bin_stock_qtys[*to] > 0 && !failed_attempts.contains(&(*from, *to)))
+            .choose(rng);
+
+        let Some((lkey, from_bin_id, to_bin_id)) = candidate else {
+            info!("[DWNS] no more sheets to downsize");
+            break;
+        };
+
+        match attempt_to_downsize(prob, lkey, to_bin_id, sep, term) {
+            true => {
+                best = prob.save();
+                info!(
+                    "[DWNS] success, transferred sheet of type {} to {} (cost: {})",
+                    from_bin_id,
+                    to_bin_id,
+                    bpp_cost(prob)
+                );
+            }
+            false => {
+                prob.restore(&best);
+                failed_attempts.push((from_bin_id, to_bin_id));
+                info!("[DWNS] failed to transfer sheet of type {} to {}", from_bin_id, to_bin_id);
+            }
+        }
+    }
+    prob.restore(&best);
+
+    info!(
+        "[DWNS] finished, reduced cost from {} to {}",
+        init_cost,
+        bpp_cost(prob)
+    );
+
+    best
+}
+
+/// Moves all items from the sheet to a new sheet of another type, keeping their position relative to the
+/// bottom-left corner of the sheet, and separates the new sheet. Returns whether all collisions were resolved.
+fn attempt_to_downsize(
+    prob: &mut BPProblem,
+    lkey: LayKey,
+    to_bin_id: usize,
+    sep: &mut BinSeparator,
+    term: &impl Terminator,
+) -> bool {
+    let old_bbox = prob.layouts[lkey].container.outer_cd.bbox;
+    let new_bbox = prob.instance.bins[to_bin_id].container.outer_cd.bbox;
+    let delta = (new_bbox.x_min - old_bbox.x_min, new_bbox.y_min - old_bbox.y_min);
+
+    let placements = prob.layouts[lkey]
+        .placed_items
+        .values()
+        .map(|pi| (pi.item_id, pi.d_transf.compose().translate(delta).decompose()))
+        .collect_vec();
+
+    prob.remove_layout(lkey);
+
+    //the first item opens the new sheet, the others follow
+    let mut layout_id = BPLayoutType::Closed { bin_id: to_bin_id };
+    for (item_id, d_transf) in placements {
+        let (new_lkey, _) = prob.place_item(BPPlacement {
+            layout_id,
+            item_id,
+            d_transf,
+        });
+        layout_id = BPLayoutType::Open(new_lkey);
+    }
+
+    match layout_id {
+        BPLayoutType::Open(new_lkey) => separate_bin(prob, new_lkey, sep, term),
+        BPLayoutType::Closed { .. } => true,
+    }
+}
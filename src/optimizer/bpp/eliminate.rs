@@ -0,0 +1,156 @@
+use crate::config::ExplorationConfig;
+use crate::consts::LBF_SAMPLE_CONFIG;
+use crate::optimizer::bpp::lbf::find_lbf_placement;
+use crate::optimizer::bpp::{BinSeparator, bpp_cost, separate_bin};
+use crate::sample::uniform_sampler::UniformBBoxSampler;
+use crate::util::terminator::Terminator;
+use itertools::Itertools;
+use jagua_rs::entities::Instance;
+use jagua_rs::geometry::DTransformation;
+use jagua_rs::probs::bpp::entities::{BPInstance, BPLayoutType, BPPlacement, BPProblem, BPSolution, LayKey};
+use log::info;
+use ordered_float::OrderedFloat;
+use rand::prelude::Distribution;
+use rand::Rng;
+use rand_distr::Normal;
+use std::cmp::Reverse;
+
+/// Bin packing analogue of the exploration phase.
+/// Repeatedly tries to empty one of the least-filled sheets by redistributing its items over the other sheets.
+/// Returns the best feasible solution found.
+pub fn elimination_phase(
+    instance: &BPInstance,
+    prob: &mut BPProblem,
+    sep: &mut BinSeparator,
+    rng: &mut impl Rng,
+    term: &impl Terminator,
+    config: &ExplorationConfig,
+) -> BPSolution {
+    let mut best = prob.save();
+    let mut n_conseq_fails = 0;
+
+    info!(
+        "[ELIM] starting elimination with {} sheets (cost: {})",
+        prob.layouts.len(),
+        bpp_cost(prob)
+    );
+
+    while !term.kill() && prob.layouts.len() > 1 {
+        //select a sheet to empty, emptier sheets have more chance to be selected
+        let victim = {
+            let sorted_lkeys = prob
+                .layouts
+                .iter()
+                .sorted_by_key(|(_, l)| OrderedFloat(l.density(instance)))
+                .map(|(lkey, _)| lkey)
+                .collect_vec();
+
+            let distr = Normal::new(0.0, config.solution_pool_distribution_stddev).unwrap();
+            let sample = distr.sample(rng).abs().min(0.999);
+            sorted_lkeys[(sample * sorted_lkeys.len() as f32) as usize]
+        };
+
+        match attempt_to_empty_bin(prob, victim, sep, rng, term) {
+            true => {
+                best = prob.save();
+                n_conseq_fails = 0;
+                info!(
+                    "[ELIM] emptied a sheet! ({} sheets, cost: {})",
+                    prob.layouts.len(),
+                    bpp_cost(prob)
+                );
+            }
+            false => {
+                prob.restore(&best);
+                n_conseq_fails += 1;
+                info!("[ELIM] unable to empty sheet ({} consecutive failures)", n_conseq_fails);
+
+                if n_conseq_fails >= config.max_conseq_failed_attempts.unwrap_or(usize::MAX) {
+                    info!("[ELIM] max consecutive failed attempts ({}), terminating", n_conseq_fails);
+                    break;
+                }
+            }
+        }
+    }
+    prob.restore(&best);
+
+    info!(
+        "[ELIM] finished, best feasible solution: {} sheets (cost: {}, dens: {:.3}%)",
+        prob.layouts.len(),
+        bpp_cost(prob),
+        best.density(instance) * 100.0
+    );
+
+    best
+}
+
+/// Removes the sheet and reinserts its items in the remaining sheets.
+/// Items which do not fit in any of the remaining sheets are inserted in the least-filled one, after which all
+/// affected sheets are separated. Returns whether all collisions were resolved.
+fn attempt_to_empty_bin(
+    prob: &mut BPProblem,
+    victim: LayKey,
+    sep: &mut BinSeparator,
+    rng: &mut impl Rng,
+    term: &impl Terminator,
+) -> bool {
+    let instance = prob.instance.clone();
+
+    //collect the items of the sheet, largest first
+    let item_ids = prob.layouts[victim]
+        .placed_items
+        .values()
+        .map(|pi| pi.item_id)
+        .sorted_by_key(|id| Reverse(OrderedFloat(instance.item(*id).shape_cd.area)))
+        .collect_vec();
+
+    prob.remove_layout(victim);
+
+    let mut affected_lkeys = vec![];
+    for item_id in item_ids {
+        let item = instance.item(item_id);
+        let (lkey, d_transf) = match find_clear_placement(prob, item_id, rng) {
+            Some(placement) => placement,
+            None => {
+                //no collision-free position available, insert it anyway in the least-filled sheet
+                let lkey = prob
+                    .layouts
+                    .iter()
+                    .min_by_key(|(_, l)| OrderedFloat(l.density(&instance)))
+                    .map(|(lkey, _)| lkey)
+                    .unwrap();
+                let c_bbox = prob.layouts[lkey].container.outer_cd.bbox;
+                let d_transf = match UniformBBoxSampler::new(c_bbox, item, c_bbox, None) {
+                    Some(sampler) => sampler.sample(rng),
+                    None => return false, //item does not fit in this sheet at all
+                };
+                if !affected_lkeys.contains(&lkey) {
+                    affected_lkeys.push(lkey);
+                }
+                (lkey, d_transf)
+            }
+        };
+        prob.place_item(BPPlacement {
+            layout_id: BPLayoutType::Open(lkey),
+            item_id,
+            d_transf,
+        });
+    }
+
+    //separate all sheets in which collisions were introduced
+    affected_lkeys
+        .into_iter()
+        .all(|lkey| separate_bin(prob, lkey, sep, term))
+}
+
+/// Searches the first sheet in which the item can be placed without any collisions.
+fn find_clear_placement(
+    prob: &BPProblem,
+    item_id: usize,
+    rng: &mut impl Rng,
+) -> Option<(LayKey, DTransformation)> {
+    let item = prob.instance.item(item_id);
+    prob.layouts.iter().find_map(|(lkey, layout)| {
+        find_lbf_placement(layout, item, LBF_SAMPLE_CONFIG, rng).map(|dt| (lkey, dt))
+    })
+}
@@ -0,0 +1,126 @@
+use crate::Instant;
+use crate::eval::lbf_evaluator::LBFEvaluator;
+use crate::eval::sample_eval::SampleEval;
+use crate::sample::search::{SampleConfig, search_placement};
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use jagua_rs::entities::{Instance, Item, Layout};
+use jagua_rs::geometry::DTransformation;
+use jagua_rs::probs::bpp::entities::{BPInstance, BPLayoutType, BPPlacement, BPProblem};
+use log::debug;
+use ordered_float::OrderedFloat;
+use rand::Rng;
+use rand_chacha::ChaCha20Rng;
+use std::cmp::Reverse;
+use std::iter;
+
+/// Left-Bottom-Fill constructor for bin packing problems.
+/// Items are placed in the first open sheet in which they fit, if none exists a new sheet is opened.
+pub struct BPLBFBuilder {
+    pub instance: BPInstance,
+    pub prob: BPProblem,
+    pub rng: ChaCha20Rng,
+    pub sample_config: SampleConfig,
+}
+
+impl BPLBFBuilder {
+    pub fn new(instance: BPInstance, rng: ChaCha20Rng, sample_config: SampleConfig) -> Self {
+        let prob = BPProblem::new(instance.clone());
+
+        Self {
+            instance,
+            prob,
+            rng,
+            sample_config,
+        }
+    }
+
+    /// Fails if some item does not fit in any of the sheets
+    pub fn construct(mut self) -> Result<Self> {
+        let start = Instant::now();
+        let n_items = self.instance.items.len();
+        let sorted_item_indices = (0..n_items)
+            .sorted_by_cached_key(|id| {
+                let item_shape = self.instance.item(*id).shape_cd.as_ref();
+                let convex_hull_area = item_shape.surrogate().convex_hull_area;
+                let diameter = item_shape.diameter;
+                Reverse(OrderedFloat(convex_hull_area * diameter))
+            })
+            .map(|id| {
+                let missing_qty = self.prob.item_demand_qtys[id];
+                iter::repeat(id).take(missing_qty)
+            })
+            .flatten()
+            .collect_vec();
+
+        debug!("[BPP-CONSTR] placing items in order: {:?}", sorted_item_indices);
+
+        for item_id in sorted_item_indices {
+            self.place_item(item_id)?;
+        }
+
+        debug!(
+            "[BPP-CONSTR] placed all items in {} sheets (in {:?})",
+            self.prob.layouts.len(),
+            start.elapsed()
+        );
+        Ok(self)
+    }
+
+    fn place_item(&mut self, item_id: usize) -> Result<()> {
+        let item = self.instance.item(item_id);
+
+        // First try to fit the item into one of the already opened sheets
+        let open_placement = self.prob.layouts.iter().find_map(|(lkey, layout)| {
+            find_lbf_placement(layout, item, self.sample_config, &mut self.rng)
+                .map(|dt| (BPLayoutType::Open(lkey), dt))
+        });
+
+        // Otherwise, open the sheet with the lowest cost per unit of area in which the item fits
+        let placement = open_placement.or_else(|| {
+            self.instance
+                .bins
+                .iter()
+                .filter(|bin| self.prob.bin_stock_qtys[bin.id] > 0)
+                .sorted_by_key(|bin| OrderedFloat(bin.cost as f32 / bin.container.outer_cd.area))
+                .find_map(|bin| {
+                    let empty_layout = Layout::new(bin.container.clone());
+                    find_lbf_placement(&empty_layout, item, self.sample_config, &mut self.rng)
+                        .map(|dt| (BPLayoutType::Closed { bin_id: bin.id }, dt))
+                })
+        });
+
+        match placement {
+            Some((layout_id, d_transf)) => {
+                let (lkey, _) = self.prob.place_item(BPPlacement {
+                    layout_id,
+                    item_id,
+                    d_transf,
+                });
+                debug!(
+                    "[BPP-CONSTR] placing item with id {} in sheet {:?} at [{}]",
+                    item_id, lkey, d_transf
+                );
+                Ok(())
+            }
+            None => bail!("item {item_id} does not fit in any of the available sheets"),
+        }
+    }
+}
+
+/// Searches for a collision-free placement of the item in the layout, closest to the left-bottom corner.
+pub fn find_lbf_placement(
+    layout: &Layout,
+    item: &Item,
+    sample_config: SampleConfig,
+    rng: &mut impl Rng,
+) -> Option<DTransformation> {
+    let evaluator = LBFEvaluator::new(layout, item);
+
+    let (best_sample, _) = search_placement(layout, item, None, None, None, evaluator, sample_config, rng);
+
+    match best_sample {
+        Some((d_transf, SampleEval::Clear { .. })) => Some(d_transf),
+        _ => None,
+    }
+}
@@ -0,0 +1,136 @@
+use crate::config::{CompressionConfig, ExplorationConfig};
+use crate::consts::LBF_SAMPLE_CONFIG;
+use crate::optimizer::bpp::downsize::downsize_phase;
+use crate::optimizer::bpp::eliminate::elimination_phase;
+use crate::optimizer::bpp::lbf::BPLBFBuilder;
+use crate::optimizer::problem::SeparableProblem;
+use crate::optimizer::separator::{Separator, SeparatorConfig};
+use crate::util::listener::DummySolListener;
+use crate::util::terminator::Terminator;
+use anyhow::Result;
+use jagua_rs::entities::{Instance, Layout, LayoutSnapshot, PItemKey};
+use jagua_rs::geometry::DTransformation;
+use jagua_rs::probs::bpp::entities::{BPInstance, BPProblem, BPSolution, LayKey};
+use log::info;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+pub mod downsize;
+pub mod eliminate;
+pub mod lbf;
+
+/// Optimizes a bin packing instance: minimizes the total cost of the sheets (bins) required to place all items.
+/// Fails if some item does not fit in any of the sheets.
+pub fn optimize_bpp(
+    instance: BPInstance,
+    mut rng: ChaCha20Rng,
+    terminator: &mut impl Terminator,
+    expl_config: &ExplorationConfig,
+    cmpr_config: &CompressionConfig,
+) -> Result<BPSolution> {
+    let mut next_rng = || ChaCha20Rng::seed_from_u64(rng.next_u64());
+    let mut builder = BPLBFBuilder::new(instance.clone(), next_rng(), LBF_SAMPLE_CONFIG).construct()?;
+
+    terminator.new_timeout(expl_config.time_limit);
+    let mut expl_sep = bin_separator(&builder.prob, next_rng(), expl_config.separator_config);
+    let expl_sol = elimination_phase(
+        &instance,
+        &mut builder.prob,
+        &mut expl_sep,
+        &mut next_rng(),
+        terminator,
+        expl_config,
+    );
+
+    terminator.new_timeout(cmpr_config.time_limit);
+    builder.prob.restore(&expl_sol);
+    let mut cmpr_sep = bin_separator(&builder.prob, next_rng(), cmpr_config.separator_config);
+    let cmpr_sol = downsize_phase(
+        &instance,
+        &mut builder.prob,
+        &mut cmpr_sep,
+        &mut next_rng(),
+        terminator,
+    );
+
+    info!(
+        "[BPP] finished, {} sheets used (cost: {}, dens: {:.3}%)",
+        cmpr_sol.layout_snapshots.len(),
+        bpp_cost(&builder.prob),
+        cmpr_sol.density(&instance) * 100.0
+    );
+
+    Ok(cmpr_sol)
+}
+
+/// A single sheet of a bin packing problem, viewed as a problem on its own so that it can be separated
+/// by a [`Separator`]. Items never leave the sheet during separation.
+#[derive(Clone)]
+pub struct BinProblem {
+    pub instance: BPInstance,
+    pub layout: Layout,
+}
+
+impl SeparableProblem for BinProblem {
+    type Instance = BPInstance;
+    type Solution = LayoutSnapshot;
+
+    fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    fn place_item(&mut self, item_id: usize, d_transf: DTransformation) -> PItemKey {
+        self.layout.place_item(self.instance.item(item_id), d_transf)
+    }
+
+    fn remove_item(&mut self, pk: PItemKey) {
+        self.layout.remove_item(pk);
+    }
+
+    fn save(&mut self) -> LayoutSnapshot {
+        self.layout.save()
+    }
+
+    fn restore(&mut self, sol: &LayoutSnapshot) {
+        self.layout.restore(sol);
+    }
+}
+
+/// Separator for the sheets of a bin packing problem, which is reused for every sheet it separates
+pub type BinSeparator = Separator<BinProblem>;
+
+/// Creates a separator for the sheets of the problem, initially loaded with an empty sheet
+pub fn bin_separator(prob: &BPProblem, rng: ChaCha20Rng, config: SeparatorConfig) -> BinSeparator {
+    let bin_prob = BinProblem {
+        instance: prob.instance.clone(),
+        layout: Layout::new(prob.instance.bins[0].container.clone()),
+    };
+    Separator::new(prob.instance.clone(), bin_prob, rng, config)
+}
+
+/// Attempts to resolve all collisions in a single sheet of the problem.
+/// If successful, the separated sheet is written back into the problem.
+pub fn separate_bin(prob: &mut BPProblem, lkey: LayKey, sep: &mut BinSeparator, term: &impl Terminator) -> bool {
+    sep.reload(BinProblem {
+        instance: prob.instance.clone(),
+        layout: prob.layouts[lkey].clone(),
+    });
+    let (sol, ct) = sep.separate(term, &mut DummySolListener);
+
+    match ct.get_total_loss() == 0.0 {
+        true => {
+            sep.prob.restore(&sol);
+            prob.layouts[lkey] = sep.prob.layout.clone();
+            true
+        }
+        false => false,
+    }
+}
+
+/// Total cost of all sheets currently in use
+pub fn bpp_cost(prob: &BPProblem) -> u64 {
+    prob.layouts
+        .values()
+        .map(|l| prob.instance.bins[l.container.id].cost)
+        .sum()
+}
@@ -0,0 +1,452 @@
+use std::cmp::Reverse;
+use float_cmp::approx_eq;
+use itertools::Itertools;
+use jagua_rs::collision_detection::hazards::HazardEntity;
+use jagua_rs::entities::PItemKey;
+use jagua_rs::geometry::DTransformation;
+use jagua_rs::geometry::geo_traits::CollidesWith;
+use jagua_rs::geometry::primitives::Rect;
+use log::{debug, info};
+use ordered_float::OrderedFloat;
+use rand::Rng;
+use rand::prelude::IteratorRandom;
+use slotmap::SecondaryMap;
+use crate::config::ExplorationConfig;
+use crate::consts::{DSRP_N_RELOCATED_ITEMS, DSRP_RUIN_WIDTH_RATIO, DSRP_UCB_EXPLORATION_FACTOR};
+use crate::optimizer::problem::{SeparableProblem, StripProblem};
+use crate::optimizer::separator::Separator;
+use crate::quantify::allowed_bbox;
+use crate::sample::uniform_sampler::{UniformBBoxSampler, convert_sample_to_closest_feasible};
+
+/// Disrupts an infeasible solution during the exploration phase, to escape from its local minimum
+pub trait DisruptionOperator {
+    fn disrupt(&self, sep: &mut Separator, config: &ExplorationConfig);
+}
+
+/// Built-in disruption operators, selectable through [`ExplorationConfig::disruptions`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Disruption {
+    /// Swap two large items, carrying along the items they practically contain
+    SwapLarge,
+    /// Move a few random items to a random position in the strip
+    Relocate,
+    /// Scatter all items within a vertical band of the strip randomly over that band
+    RuinRecreate,
+    /// Rotate a large item by 180 degrees around its point of inaccessibility
+    FlipLarge,
+    /// Swap two horizontally neighbouring items, reversing their order along x
+    SwapAlongX,
+}
+
+impl Disruption {
+    pub const ALL: [Disruption; 5] = [
+        Disruption::SwapLarge,
+        Disruption::Relocate,
+        Disruption::RuinRecreate,
+        Disruption::FlipLarge,
+        Disruption::SwapAlongX,
+    ];
+
+    pub fn operator(&self) -> &'static dyn DisruptionOperator {
+        match self {
+            Disruption::SwapLarge => &SwapLargeItems,
+            Disruption::Relocate => &RelocateItems,
+            Disruption::RuinRecreate => &RuinAndRecreate,
+            Disruption::FlipLarge => &FlipLargeItem,
+            Disruption::SwapAlongX => &SwapAlongX,
+        }
+    }
+}
+
+/// Set of built-in disruption operators, stored as a bitmask so that the configuration stays [`Copy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DisruptionSet(u8);
+
+impl DisruptionSet {
+    pub const fn single(disruption: Disruption) -> Self {
+        Self(1 << disruption as u8)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, disruption: Disruption) -> bool {
+        self.0 & (1 << disruption as u8) != 0
+    }
+
+    /// The operators in the set, in the order of [`Disruption::ALL`]
+    pub fn iter(self) -> impl Iterator<Item = Disruption> {
+        Disruption::ALL.into_iter().filter(move |d| self.contains(*d))
+    }
+}
+
+impl FromIterator<Disruption> for DisruptionSet {
+    fn from_iter<I: IntoIterator<Item = Disruption>>(iter: I) -> Self {
+        Self(iter.into_iter().fold(0, |mask, d| mask | Self::single(d).0))
+    }
+}
+
+/// Adaptive selection of the disruption operators: a multi-armed bandit (UCB1) which credits an operator
+/// every time the separation following its disruption reaches feasibility.
+pub struct DisruptionSelector {
+    pub stats: Vec<DisruptionStats>,
+    /// Operator which disrupted the layout currently being separated
+    pending: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DisruptionStats {
+    pub disruption: Disruption,
+    pub n_applied: usize,
+    pub n_success: usize,
+}
+
+impl DisruptionStats {
+    pub fn success_rate(&self) -> f32 {
+        match self.n_applied {
+            0 => 0.0,
+            n => self.n_success as f32 / n as f32,
+        }
+    }
+}
+
+impl DisruptionSelector {
+    pub fn new(disruptions: DisruptionSet) -> Self {
+        assert!(!disruptions.is_empty(), "no disruption operators configured");
+        let stats = disruptions
+            .iter()
+            .map(|d| DisruptionStats { disruption: d, n_applied: 0, n_success: 0 })
+            .collect();
+        Self { stats, pending: None }
+    }
+
+    /// Selects the operator to apply next: every operator is tried once, afterwards the one with the highest upper confidence bound
+    pub fn select(&mut self) -> Disruption {
+        let n_total = self.stats.iter().map(|s| s.n_applied).sum::<usize>() as f32;
+        let idx = match self.stats.iter().position(|s| s.n_applied == 0) {
+            Some(untried) => untried,
+            None => self
+                .stats
+                .iter()
+                .position_max_by_key(|s| {
+                    let bonus = DSRP_UCB_EXPLORATION_FACTOR * (n_total.ln() / s.n_applied as f32).sqrt();
+                    OrderedFloat(s.success_rate() + bonus)
+                })
+                .unwrap(),
+        };
+        self.stats[idx].n_applied += 1;
+        self.pending = Some(idx);
+        self.stats[idx].disruption
+    }
+
+    /// Reports the outcome of the separation following the last disruption
+    pub fn report(&mut self, feasible: bool) {
+        if let Some(idx) = self.pending.take() {
+            if feasible {
+                self.stats[idx].n_success += 1;
+            }
+        }
+    }
+}
+
+pub struct SwapLargeItems;
+
+impl DisruptionOperator for SwapLargeItems {
+    fn disrupt(&self, sep: &mut Separator, config: &ExplorationConfig) {
+        // The general idea is to disrupt a solution by swapping two 'large' items in the layout.
+        // 'Large' items are those whose convex hull area falls within a certain top percentile
+        // of the total convex hull area of all items in the layout.
+
+        // Pinned items are never moved, at least two movable items are required for a swap.
+        let n_movable = sep.prob.layout.placed_items.keys().filter(|pk| !sep.prob.is_pinned(*pk)).count();
+        if n_movable < 2 {
+            debug!("[DSRP] less than two movable items, no swap possible");
+            return;
+        }
+
+        // Step 1: Define what constitutes a 'large' item.
+        let ch_area_cutoff = large_item_ch_area_cutoff(sep, config.large_item_ch_area_cutoff_percentile);
+
+        // Step 2: Select two 'large' items and 'swap' them.
+
+        let movable_items = sep.prob.layout.placed_items.iter()
+            .filter(|(pk, _)| !sep.prob.is_pinned(*pk));
+
+        let large_items = movable_items.clone()
+            .filter(|(_, pi)| pi.shape.surrogate().convex_hull_area >= ch_area_cutoff);
+
+        //Choose a first item with a large enough convex hull
+        let (pk1, pi1) = large_items.clone().choose(&mut sep.rng).expect("[DSRP] failed to choose first item");
+
+        //Choose a second item with a large enough convex hull and different enough from the first.
+        //If no such item is found, choose a random one.
+        let (pk2, pi2) = large_items.clone()
+            .filter(|(_, pi)|
+                // Ensure the second item is different from the first
+                !approx_eq!(f32, pi.shape.area,pi1.shape.area, epsilon = pi1.shape.area * 0.01) &&
+                    !approx_eq!(f32, pi.shape.diameter, pi1.shape.diameter, epsilon = pi1.shape.diameter * 0.01)
+            )
+            .choose(&mut sep.rng)
+            .or_else(|| {
+                movable_items.clone()
+                    .filter(|(pk, _)| *pk != pk1) // Ensure the second item is not the same as the first
+                    .choose(&mut sep.rng)
+            }) // As a fallback, choose any item
+            .expect("[DSRP] failed to choose second item");
+
+        // Step 3: Swap the two items' positions in the layout.
+
+        let dt1_old = pi1.d_transf;
+        let dt2_old = pi2.d_transf;
+
+        // Items which can be mirrored are swapped in a random variant.
+        let mut random_variant = |item_id: usize| match sep.prob.item_features.mirror_twin(item_id) {
+            Some(twin_id) if sep.rng.random_bool(0.5) => twin_id,
+            _ => item_id,
+        };
+        let id1_new = random_variant(pi1.item_id);
+        let id2_new = random_variant(pi2.item_id);
+
+        // Make sure the swaps do not violate feasibility (rotation).
+        let dt1_new = convert_sample_to_closest_feasible(dt2_old, sep.prob.instance.item(id1_new), sep.prob.item_features.rot_intervals(id1_new));
+        let dt2_new = convert_sample_to_closest_feasible(dt1_old, sep.prob.instance.item(id2_new), sep.prob.item_features.rot_intervals(id2_new));
+
+        info!("[EXPL] disrupting by swapping two large items (id: {} <-> {})", pi1.item_id, pi2.item_id);
+
+        let pk1 = sep.move_item_as(pk1, id1_new, dt1_new);
+        let pk2 = sep.move_item_as(pk2, id2_new, dt2_new);
+
+
+        // Step 4: Move all items that are practically contained by one of the swapped items to the "empty space" created by the moved item.
+        //         This is particularly important when huge items are swapped with smaller items.
+        //         The huge item will create a large empty space and many of the items which previously
+        //         surrounded the smaller one will be contained by the huge one.
+        {
+            // transformation to convert the contained items' position (relative to the old and new positions of the swapped items)
+            let converting_transformation = dt1_new.compose().inverse()
+                .transform(&dt1_old.compose());
+
+            for c1_pk in practically_contained_items(&sep.prob, pk1).into_iter().filter(|c1_pk| *c1_pk != pk2) {
+                let c1_pi = &sep.prob.layout.placed_items[c1_pk];
+
+                let new_dt = c1_pi.d_transf
+                    .compose()
+                    .transform(&converting_transformation)
+                    .decompose();
+
+                //Ensure the sure the new position is feasible
+                let new_feasible_dt = convert_sample_to_closest_feasible(new_dt, sep.prob.instance.item(c1_pi.item_id), sep.prob.item_features.rot_intervals(c1_pi.item_id));
+                sep.move_item(c1_pk, new_feasible_dt);
+            }
+        }
+
+        // Do the same for the second item, but using the second transformation
+        {
+            let converting_transformation = dt2_new.compose().inverse()
+                .transform(&dt2_old.compose());
+
+            for c2_pk in practically_contained_items(&sep.prob, pk2).into_iter().filter(|c2_pk| *c2_pk != pk1) {
+                let c2_pi = &sep.prob.layout.placed_items[c2_pk];
+                let new_dt = c2_pi.d_transf
+                    .compose()
+                    .transform(&converting_transformation)
+                    .decompose();
+
+                //make sure the new position is feasible
+                let new_feasible_dt = convert_sample_to_closest_feasible(new_dt, sep.prob.instance.item(c2_pi.item_id), sep.prob.item_features.rot_intervals(c2_pi.item_id));
+                sep.move_item(c2_pk, new_feasible_dt);
+            }
+        }
+    }
+}
+
+pub struct RelocateItems;
+
+impl DisruptionOperator for RelocateItems {
+    fn disrupt(&self, sep: &mut Separator, _config: &ExplorationConfig) {
+        let pks = sep.prob.layout.placed_items.keys()
+            .filter(|pk| !sep.prob.is_pinned(*pk))
+            .choose_multiple(&mut sep.rng, DSRP_N_RELOCATED_ITEMS);
+
+        info!("[EXPL] disrupting by relocating {} random items", pks.len());
+
+        let c_bbox = sep.prob.layout.container.outer_cd.bbox;
+        for pk in pks {
+            let item_id = sep.prob.layout.placed_items[pk].item_id;
+            let bbox = allowed_bbox(c_bbox, sep.prob.item_features.region(item_id));
+            move_to_random_position(sep, pk, bbox, bbox);
+        }
+    }
+}
+
+pub struct RuinAndRecreate;
+
+impl DisruptionOperator for RuinAndRecreate {
+    fn disrupt(&self, sep: &mut Separator, _config: &ExplorationConfig) {
+        let c_bbox = sep.prob.layout.container.outer_cd.bbox;
+
+        //the band is centered around a random item, so that it is never empty
+        let Some(center_x) = sep.prob.layout.placed_items.iter()
+            .filter(|(pk, _)| !sep.prob.is_pinned(*pk))
+            .map(|(_, pi)| pi.shape.poi.center.0)
+            .choose(&mut sep.rng) else {
+            return;
+        };
+        let half_width = c_bbox.width() * DSRP_RUIN_WIDTH_RATIO / 2.0;
+        let band = Rect::try_new(
+            f32::max(c_bbox.x_min, center_x - half_width),
+            c_bbox.y_min,
+            f32::min(c_bbox.x_max, center_x + half_width),
+            c_bbox.y_max,
+        ).unwrap();
+
+        let ruined = sep.prob.layout.placed_items.iter()
+            .filter(|(pk, _)| !sep.prob.is_pinned(*pk))
+            .filter(|(_, pi)| (band.x_min..=band.x_max).contains(&pi.shape.poi.center.0))
+            .map(|(pk, _)| pk)
+            .collect_vec();
+
+        info!("[EXPL] disrupting by ruining {} items in band [{:.3}, {:.3}]", ruined.len(), band.x_min, band.x_max);
+
+        //recreate the band by scattering its items randomly over it, the separator resolves the overlap
+        for pk in ruined {
+            let item_id = sep.prob.layout.placed_items[pk].item_id;
+            let bbox = allowed_bbox(c_bbox, sep.prob.item_features.region(item_id));
+            move_to_random_position(sep, pk, band, bbox);
+        }
+    }
+}
+
+pub struct FlipLargeItem;
+
+impl DisruptionOperator for FlipLargeItem {
+    fn disrupt(&self, sep: &mut Separator, config: &ExplorationConfig) {
+        let ch_area_cutoff = large_item_ch_area_cutoff(sep, config.large_item_ch_area_cutoff_percentile);
+
+        let Some((pk, pi)) = sep.prob.layout.placed_items.iter()
+            .filter(|(pk, _)| !sep.prob.is_pinned(*pk))
+            .filter(|(_, pi)| pi.shape.surrogate().convex_hull_area >= ch_area_cutoff)
+            .choose(&mut sep.rng) else {
+            return;
+        };
+
+        let item = sep.prob.instance.item(pi.item_id);
+        let flipped = DTransformation::new(pi.d_transf.rotation() + std::f32::consts::PI, pi.d_transf.translation());
+        let r = convert_sample_to_closest_feasible(flipped, item, sep.prob.item_features.rot_intervals(pi.item_id)).rotation();
+
+        //keep the point of inaccessibility of the item in place
+        let poi = pi.shape.poi.center;
+        let local_poi = item.shape_cd.poi.center;
+        let (sin, cos) = r.sin_cos();
+        let new_dt = DTransformation::new(r, (
+            poi.0 - (cos * local_poi.0 - sin * local_poi.1),
+            poi.1 - (sin * local_poi.0 + cos * local_poi.1),
+        ));
+
+        info!("[EXPL] disrupting by flipping a large item (id: {}, r: {:.3} -> {:.3})", pi.item_id, pi.d_transf.rotation(), r);
+        sep.move_item(pk, new_dt);
+    }
+}
+
+pub struct SwapAlongX;
+
+impl DisruptionOperator for SwapAlongX {
+    fn disrupt(&self, sep: &mut Separator, _config: &ExplorationConfig) {
+        let movable = sep.prob.layout.placed_items.iter()
+            .filter(|(pk, _)| !sep.prob.is_pinned(*pk))
+            .map(|(pk, pi)| (pk, pi.shape.bbox))
+            .collect_vec();
+
+        //the right neighbour of an item: the closest item to its right which overlaps with it vertically
+        let right_neighbour = |bbox: &Rect| movable.iter()
+            .filter(|(_, b)| b.x_min > bbox.x_min && b.y_min < bbox.y_max && b.y_max > bbox.y_min)
+            .min_by_key(|(_, b)| OrderedFloat(b.x_min))
+            .copied();
+
+        let Some(((pk1, bbox1), (pk2, bbox2))) = movable.iter()
+            .filter_map(|(pk, bbox)| right_neighbour(bbox).map(|n| ((*pk, *bbox), n)))
+            .choose(&mut sep.rng) else {
+            return;
+        };
+
+        //the second item moves to the left of the span both items cover, the first one to the right
+        let dx1 = bbox2.x_max - bbox1.x_max;
+        let dx2 = bbox1.x_min - bbox2.x_min;
+
+        let (id1, id2) = (sep.prob.layout.placed_items[pk1].item_id, sep.prob.layout.placed_items[pk2].item_id);
+        info!("[EXPL] disrupting by swapping two neighbouring items along x (id: {} <-> {})", id1, id2);
+
+        for (pk, dx) in [(pk1, dx1), (pk2, dx2)] {
+            let dt = sep.prob.layout.placed_items[pk].d_transf;
+            let (tx, ty) = dt.translation();
+            sep.move_item(pk, DTransformation::new(dt.rotation(), (tx + dx, ty)));
+        }
+    }
+}
+
+/// Convex hull area from which a movable item is considered 'large': the large items together make up
+/// the given percentile of the total convex hull area of all movable (non-pinned) items in the layout.
+fn large_item_ch_area_cutoff(sep: &Separator, percentile: f32) -> f32 {
+    // Sort the movable items by convex hull area in descending order.
+    let sorted_ch_areas = sep.prob.layout.placed_items.iter()
+        .filter(|(pk, _)| !sep.prob.is_pinned(*pk))
+        .map(|(_, pi)| pi.shape.surrogate().convex_hull_area)
+        .sorted_by_key(|ch_area| Reverse(OrderedFloat(*ch_area)))
+        .collect_vec();
+
+    let cutoff_threshold_area = sorted_ch_areas.iter().sum::<f32>() * percentile;
+
+    let mut cumulative_ch_area = 0.0;
+    let mut ch_area_cutoff = 0.0;
+
+    // Iterate through items, accumulating their convex hull areas until the cumulative sum
+    // exceeds the cutoff_threshold_area. The convex hull area of the item that causes
+    // this excess becomes the ch_area_cutoff.
+    for ch_area in sorted_ch_areas {
+        cumulative_ch_area += ch_area;
+        if cumulative_ch_area > cutoff_threshold_area {
+            ch_area_cutoff = ch_area;
+            debug!("[DSRP] cutoff ch area: {}", ch_area_cutoff);
+            break;
+        }
+    }
+    ch_area_cutoff
+}
+
+/// Moves the item to a random position within `sample_bbox`, keeping it inside `container_bbox`.
+/// The item stays in place if it does not fit anywhere.
+fn move_to_random_position(sep: &mut Separator, pk: PItemKey, sample_bbox: Rect, container_bbox: Rect) {
+    let item_id = sep.prob.layout.placed_items[pk].item_id;
+    let item = sep.prob.instance.item(item_id);
+    if let Some(sampler) = UniformBBoxSampler::new(sample_bbox, item, container_bbox, sep.prob.item_features.rot_intervals(item_id)) {
+        let dt = sampler.sample(&mut sep.rng);
+        sep.move_item(pk, dt);
+    }
+}
+
+/// Collects all (non-pinned) items which point of inaccessibility (POI) is contained by pk_c's shape.
+fn practically_contained_items(prob: &StripProblem, pk_c: PItemKey) -> Vec<PItemKey> {
+    let layout = &prob.layout;
+    let pi_c = &layout.placed_items[pk_c];
+    // Detect all collisions with the item pk_c's shape.
+    let mut collector = SecondaryMap::new();
+    layout.cde().collect_poly_collisions(&pi_c.shape, &mut collector);
+
+    // Filter out the items that have their POI contained by pk_c's shape.
+    collector.iter()
+        .filter_map(|(_,he)| {
+            match he {
+                HazardEntity::PlacedItem { pk, .. } => Some(*pk),
+                _ => None
+            }
+        })
+        .filter(|pk| *pk != pk_c) // Ensure we don't include the item itself
+        .filter(|pk| !prob.is_pinned(*pk))
+        .filter(|pk| {
+            // Check if the POI of the item is contained by pk_c's shape
+            let poi = layout.placed_items[*pk].shape.poi;
+            pi_c.shape.collides_with(&poi.center)
+        })
+        .collect_vec()
+}
@@ -1,19 +1,13 @@
-use std::cmp::Reverse;
-use float_cmp::approx_eq;
-use itertools::Itertools;
-use jagua_rs::collision_detection::hazards::HazardEntity;
-use jagua_rs::entities::{Instance, Layout, PItemKey};
-use jagua_rs::geometry::geo_traits::CollidesWith;
+use jagua_rs::entities::Instance;
 use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
-use log::{debug, info};
-use ordered_float::OrderedFloat;
-use rand::prelude::{Distribution, IteratorRandom};
+use log::info;
+use rand::prelude::Distribution;
 use rand_distr::Normal;
-use slotmap::SecondaryMap;
-use crate::config::ExplorationConfig;
+use crate::config::{ExplorationConfig, WidthSchedule};
 use crate::FMT;
-use crate::optimizer::separator::{Separator, SeparatorConfig};
-use crate::sample::uniform_sampler::convert_sample_to_closest_feasible;
+use crate::optimizer::disrupt::DisruptionSelector;
+use crate::optimizer::problem::SeparableProblem;
+use crate::optimizer::separator::Separator;
 use crate::util::listener::{ReportType, SolutionListener};
 use crate::util::terminator::Terminator;
 
@@ -27,10 +21,13 @@ pub fn exploration_phase(instance: &SPInstance, sep: &mut Separator, sol_listene
     info!("[EXPL] starting optimization with initial width: {:.3} ({:.3}%)",current_width,sep.prob.density() * 100.0);
 
     let mut solution_pool: Vec<(SPSolution, f32)> = vec![];
+    let mut selector = DisruptionSelector::new(config.disruptions);
+    let mut width_search = WidthSearch::new(config, current_width, full_density_width(sep));
 
     while !term.kill() {
         let local_best = sep.separate(term, sol_listener);
         let total_loss = local_best.1.get_total_loss();
+        selector.report(total_loss == 0.0);
 
         if total_loss == 0.0 {
             //layout is successfully separated
@@ -40,8 +37,8 @@ pub fn exploration_phase(instance: &SPInstance, sep: &mut Separator, sol_listene
                 feasible_solutions.push(local_best.0.clone());
                 sol_listener.report(ReportType::ExplFeas, &local_best.0, instance);
             }
-            let next_width = current_width * (1.0 - config.shrink_step);
-            info!("[EXPL] shrinking strip by {}%: {:.3} -> {:.3}", config.shrink_step * 100.0, current_width, next_width);
+            let next_width = width_search.on_success(current_width);
+            info!("[EXPL] shrinking strip by {:.3}%: {:.3} -> {:.3}", (1.0 - next_width / current_width) * 100.0, current_width, next_width);
             sep.change_strip_width(next_width, None);
             current_width = next_width;
             solution_pool.clear();
@@ -59,6 +56,18 @@ pub fn exploration_phase(instance: &SPInstance, sep: &mut Separator, sol_listene
                 break;
             }
 
+            if let Some(next_width) = width_search.on_failure(current_width, solution_pool.len()) {
+                //continue from the best feasible solution at the new width
+                info!("[EXPL] giving up on width {:.3} after {} failed attempts: {:.3} -> {:.3}", current_width, solution_pool.len(), current_width, next_width);
+                let best = feasible_solutions.last().unwrap();
+                sep.change_strip_width(best.strip_width(), None);
+                sep.rollback(best, None);
+                sep.change_strip_width(next_width, None);
+                current_width = next_width;
+                solution_pool.clear();
+                continue;
+            }
+
             //restore to a random solution from the tabu list, better solutions have more chance to be selected
             let selected_sol = {
                 //sample a value in range [0.0, 1.0[ from a normal distribution
@@ -73,159 +82,98 @@ pub fn exploration_phase(instance: &SPInstance, sep: &mut Separator, sol_listene
             };
 
             sep.rollback(selected_sol, None);
-            disrupt_solution(sep, config);
+            let disruption = selector.select();
+            disruption.operator().disrupt(sep, config);
         }
     }
 
     info!("[EXPL] finished, best feasible solution: width: {:.3} ({:.3}%)",best_width,feasible_solutions.last().unwrap().density(instance) * 100.0);
 
+    for stats in selector.stats.iter() {
+        info!("[EXPL] disruption {:?}: applied {} times, {} led to feasibility ({:.1}%)", stats.disruption, stats.n_applied, stats.n_success, stats.success_rate() * 100.0);
+    }
+
     feasible_solutions
 }
 
-fn disrupt_solution(sep: &mut Separator, config: &ExplorationConfig) {
-    // The general idea is to disrupt a solution by swapping two 'large' items in the layout.
-    // 'Large' items are those whose convex hull area falls within a certain top percentile
-    // of the total convex hull area of all items in the layout.
-
-    // Step 1: Define what constitutes a 'large' item.
-
-    // Calculate the total convex hull area of all items, considering quantities.
-    let total_convex_hull_area: f32 = sep
-        .prob
-        .instance
-        .items
-        .iter()
-        .map(|(item, quantity)| item.shape_cd.surrogate().convex_hull_area * (*quantity as f32))
-        .sum();
-
-    let cutoff_threshold_area = total_convex_hull_area * config.large_item_ch_area_cutoff_percentile;
+/// Strip widths attempted by the exploration phase, following the configured [`WidthSchedule`]
+struct WidthSearch {
+    schedule: WidthSchedule,
+    shrink_step: f32,
+    /// Current step of the adaptive schedule
+    step: f32,
+    /// Bounds of the bisection: the largest given up width and the best feasible width
+    lower: f32,
+    upper: f32,
+}
 
-    // Sort items by convex hull area in descending order.
-    let sorted_items_by_ch_area = sep
-        .prob
-        .instance
-        .items
-        .iter()
-        .sorted_by_key(|(item, _)| Reverse(OrderedFloat(item.shape_cd.surrogate().convex_hull_area)))
-        .peekable();
-
-    let mut cumulative_ch_area = 0.0;
-    let mut ch_area_cutoff = 0.0;
-
-    // Iterate through items, accumulating their convex hull areas until the cumulative sum
-    // exceeds the cutoff_threshold_area. The convex hull area of the item that causes
-    // this excess becomes the ch_area_cutoff.
-    for (item, quantity) in sorted_items_by_ch_area {
-        let item_ch_area = item.shape_cd.surrogate().convex_hull_area;
-        cumulative_ch_area += item_ch_area * (*quantity as f32);
-        if cumulative_ch_area > cutoff_threshold_area {
-            ch_area_cutoff = item_ch_area;
-            debug!("[DSRP] cutoff ch area: {}, for item id: {}, bbox: {:?}",ch_area_cutoff, item.id, item.shape_cd.bbox);
-            break;
+impl WidthSearch {
+    fn new(config: &ExplorationConfig, initial_width: f32, lower_bound: f32) -> Self {
+        Self {
+            schedule: config.width_schedule,
+            shrink_step: config.shrink_step,
+            step: config.shrink_step,
+            lower: lower_bound,
+            upper: initial_width,
         }
     }
 
-    // Step 2: Select two 'large' items and 'swap' them.
-
-    let large_items = sep.prob.layout.placed_items.iter()
-        .filter(|(_, pi)| pi.shape.surrogate().convex_hull_area >= ch_area_cutoff);
-
-    //Choose a first item with a large enough convex hull
-    let (pk1, pi1) = large_items.clone().choose(&mut sep.rng).expect("[DSRP] failed to choose first item");
-
-    //Choose a second item with a large enough convex hull and different enough from the first.
-    //If no such item is found, choose a random one.
-    let (pk2, pi2) = large_items.clone()
-        .filter(|(_, pi)|
-            // Ensure the second item is different from the first
-            !approx_eq!(f32, pi.shape.area,pi1.shape.area, epsilon = pi1.shape.area * 0.01) &&
-                !approx_eq!(f32, pi.shape.diameter, pi1.shape.diameter, epsilon = pi1.shape.diameter * 0.01)
-        )
-        .choose(&mut sep.rng)
-        .or_else(|| {
-            sep.prob.layout.placed_items.iter()
-                .filter(|(pk, _)| *pk != pk1) // Ensure the second item is not the same as the first
-                .choose(&mut sep.rng)
-        }) // As a fallback, choose any item
-        .expect("[EXPL] failed to choose second item for disruption");
-
-    // Step 3: Swap the two items' positions in the layout.
-
-    let dt1_old = pi1.d_transf;
-    let dt2_old = pi2.d_transf;
-
-    // Make sure the swaps do not violate feasibility (rotation).
-    let dt1_new = convert_sample_to_closest_feasible(dt2_old, sep.prob.instance.item(pi1.item_id));
-    let dt2_new = convert_sample_to_closest_feasible(dt1_old, sep.prob.instance.item(pi2.item_id));
-
-    info!("[EXPL] disrupting by swapping two large items (id: {} <-> {})", pi1.item_id, pi2.item_id);
-
-    let pk1 = sep.move_item(pk1, dt1_new);
-    let pk2 = sep.move_item(pk2, dt2_new);
-
-
-    // Step 4: Move all items that are practically contained by one of the swapped items to the "empty space" created by the moved item.
-    //         This is particularly important when huge items are swapped with smaller items. 
-    //         The huge item will create a large empty space and many of the items which previously 
-    //         surrounded the smaller one will be contained by the huge one.
-    {
-        // transformation to convert the contained items' position (relative to the old and new positions of the swapped items)
-        let converting_transformation = dt1_new.compose().inverse()
-            .transform(&dt1_old.compose());
-
-        for c1_pk in practically_contained_items(&sep.prob.layout, pk1).into_iter().filter(|c1_pk| *c1_pk != pk2) {
-            let c1_pi = &sep.prob.layout.placed_items[c1_pk];
-
-            let new_dt = c1_pi.d_transf
-                .compose()
-                .transform(&converting_transformation)
-                .decompose();
-
-            //Ensure the sure the new position is feasible
-            let new_feasible_dt = convert_sample_to_closest_feasible(new_dt, sep.prob.instance.item(c1_pi.item_id));
-            sep.move_item(c1_pk, new_feasible_dt);
+    /// Width to attempt after a feasible solution was found at `width`
+    fn on_success(&mut self, width: f32) -> f32 {
+        match self.schedule {
+            WidthSchedule::Fixed => width * (1.0 - self.shrink_step),
+            WidthSchedule::Adaptive { growth, max_step, .. } => {
+                let next_width = width * (1.0 - self.step);
+                self.step = f32::min(self.step * growth, max_step);
+                next_width
+            }
+            WidthSchedule::Bisection { .. } => {
+                self.upper = width;
+                self.bisect()
+            }
         }
     }
 
-    // Do the same for the second item, but using the second transformation
-    {
-        let converting_transformation = dt2_new.compose().inverse()
-            .transform(&dt2_old.compose());
-
-        for c2_pk in practically_contained_items(&sep.prob.layout, pk2).into_iter().filter(|c2_pk| *c2_pk != pk1) {
-            let c2_pi = &sep.prob.layout.placed_items[c2_pk];
-            let new_dt = c2_pi.d_transf
-                .compose()
-                .transform(&converting_transformation)
-                .decompose();
-
-            //make sure the new position is feasible
-            let new_feasible_dt = convert_sample_to_closest_feasible(new_dt, sep.prob.instance.item(c2_pi.item_id));
-            sep.move_item(c2_pk, new_feasible_dt);
+    /// Called after every failed attempt at `width`, with the number of consecutive failed attempts at it.
+    /// Returns a larger width to continue from, if the schedule gives up on `width`.
+    fn on_failure(&mut self, width: f32, n_failed: usize) -> Option<f32> {
+        match self.schedule {
+            WidthSchedule::Fixed => None,
+            WidthSchedule::Adaptive { decay, min_step, .. } => {
+                self.step = f32::max(self.step * decay, min_step);
+                None
+            }
+            WidthSchedule::Bisection { max_fails } => {
+                if n_failed < max_fails || width <= self.lower {
+                    return None;
+                }
+                self.lower = width;
+                let next_width = self.bisect();
+                (next_width > width).then_some(next_width)
+            }
         }
     }
+
+    fn bisect(&self) -> f32 {
+        //shrink by at least a regular step, also once the bounds have converged
+        f32::min((self.lower + self.upper) / 2.0, self.upper * (1.0 - self.shrink_step))
+    }
 }
 
-/// Collects all items which point of inaccessibility (POI) is contained by pk_c's shape.
-fn practically_contained_items(layout: &Layout, pk_c: PItemKey) -> Vec<PItemKey> {
-    let pi_c = &layout.placed_items[pk_c];
-    // Detect all collisions with the item pk_c's shape.
-    let mut collector = SecondaryMap::new();
-    layout.cde().collect_poly_collisions(&pi_c.shape, &mut collector);
-
-    // Filter out the items that have their POI contained by pk_c's shape.
-    collector.iter()
-        .filter_map(|(_,he)| {
-            match he {
-                HazardEntity::PlacedItem { pk, .. } => Some(*pk),
-                _ => None
-            }
-        })
-        .filter(|pk| *pk != pk_c) // Ensure we don't include the item itself
-        .filter(|pk| {
-            // Check if the POI of the item is contained by pk_c's shape
-            let poi = layout.placed_items[*pk].shape.poi;
-            pi_c.shape.collides_with(&poi.center)
-        })
-        .collect_vec()
-}
\ No newline at end of file
+/// Width of the strip if all items were packed at full density
+fn full_density_width(sep: &Separator) -> f32 {
+    let item_features = &sep.prob.item_features;
+    let instance = &sep.prob.instance;
+    let item_area = |id: usize| match item_features.kit_parts(id) {
+        //the shape of a kit is the convex hull of its parts, only the parts themselves are covered
+        [] => instance.item(id).shape_cd.area,
+        parts => parts.iter().map(|part| instance.item(part.item_id).shape_cd.area).sum(),
+    };
+    let total_item_area = instance
+        .items
+        .iter()
+        .filter(|(item, _)| !item_features.is_mirrored(item.id))
+        .map(|(item, qty)| item_area(item.id) * *qty as f32)
+        .sum::<f32>();
+    total_item_area / sep.prob.strip_height()
+}
@@ -0,0 +1,55 @@
+use crate::consts::LBF_SAMPLE_CONFIG;
+use crate::optimizer::incremental::DemandDelta;
+use crate::optimizer::lbf::LBFBuilder;
+use crate::optimizer::problem::{ItemFeatures, StripFeatures};
+use crate::util::listener::{ReportType, SolutionListener};
+use anyhow::Result;
+use jagua_rs::probs::spp::entities::{SPInstance, SPPlacement, SPSolution};
+use log::info;
+use rand_chacha::ChaCha20Rng;
+
+/// Fills up the free space in the strip of a solution with the optional copies of the items (see [`ItemFeatures::extra_qtys`]),
+/// without increasing its width.
+/// Returns the instance including the optional copies, the filled solution and the number of extra items placed.
+/// Fails if the optional copies refer to unknown items.
+pub fn fill_strip(
+    instance: &SPInstance,
+    solution: &SPSolution,
+    strip_features: StripFeatures,
+    item_features: ItemFeatures,
+    rng: ChaCha20Rng,
+    sol_listener: &mut impl SolutionListener,
+) -> Result<(SPInstance, SPSolution, usize)> {
+    let delta = DemandDelta {
+        added: item_features.extra_qtys.iter().copied().enumerate().filter(|(_, qty)| *qty > 0).collect(),
+        removed: vec![],
+    };
+    let fill_instance = delta.apply(instance, &item_features)?;
+
+    let mut builder = LBFBuilder::new(fill_instance.clone(), rng, LBF_SAMPLE_CONFIG)
+        .with_strip_features(strip_features)
+        .with_item_features(item_features);
+    builder.prob.change_strip_width(solution.strip_width());
+    for pi in solution.layout_snapshot.placed_items.values() {
+        builder.prob.place_item(SPPlacement {
+            item_id: pi.item_id,
+            d_transf: pi.d_transf,
+        });
+    }
+
+    let extras = builder.missing_items();
+    let mut builder = builder.insert_items(&extras);
+    let filled = builder.prob.save();
+    let n_extras = filled.layout_snapshot.placed_items.len() - solution.layout_snapshot.placed_items.len();
+
+    info!(
+        "[FILL] placed {} of {} extra items (width: {:.3}, dens: {:.3}%)",
+        n_extras,
+        extras.len(),
+        filled.strip_width(),
+        filled.density(&fill_instance) * 100.0
+    );
+    sol_listener.report(ReportType::Final, &filled, &fill_instance);
+
+    Ok((fill_instance, filled, n_extras))
+}
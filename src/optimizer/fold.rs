@@ -0,0 +1,42 @@
+use crate::optimizer::problem::ItemFeatures;
+use jagua_rs::entities::Instance;
+use jagua_rs::io::ext_repr::ExtTransformation;
+use jagua_rs::io::export::int_to_ext_transformation;
+use jagua_rs::io::import::ext_to_int_transformation;
+use jagua_rs::probs::spp::entities::{SPInstance, SPPlacement, SPProblem, SPSolution};
+
+/// Unfolds a solution of a folded strip (fabric spread face-to-face) into a solution of the full strip.
+/// The fold line is the top edge of the folded strip: every placed item gets a mirrored copy on the other side of it.
+/// Every item of the instance should have a mirrored twin.
+pub fn unfold_solution(
+    instance: &SPInstance,
+    solution: &SPSolution,
+    item_features: &ItemFeatures,
+) -> (SPInstance, SPSolution) {
+    let fold_y = instance.strip_height;
+    //both copies of a pair count towards the demand of either variant, which is the demand of the folded item
+    let unfolded_instance = SPInstance::new(instance.items.clone(), 2.0 * fold_y);
+    let mut prob = SPProblem::new(unfolded_instance.clone());
+    prob.change_strip_width(solution.strip_width());
+
+    for pi in solution.layout_snapshot.placed_items.values() {
+        let twin_id = item_features
+            .mirror_twin(pi.item_id)
+            .expect("every item of a folded strip should have a mirrored twin");
+        //reflecting (y -> 2 * fold_y - y) the item equals mirroring it (x -> -x) and rotating it by 180° - r
+        let ext_transf = int_to_ext_transformation(&pi.d_transf, &instance.item(pi.item_id).shape_orig.pre_transform);
+        let (tx, ty) = ext_transf.translation;
+        let reflected = ExtTransformation {
+            rotation: 180.0 - ext_transf.rotation,
+            translation: (tx, 2.0 * fold_y - ty),
+        };
+        let d_transf = ext_to_int_transformation(&reflected, &instance.item(twin_id).shape_orig.pre_transform);
+
+        prob.place_item(SPPlacement {
+            item_id: pi.item_id,
+            d_transf: pi.d_transf,
+        });
+        prob.place_item(SPPlacement { item_id: twin_id, d_transf });
+    }
+    (unfolded_instance, prob.save())
+}
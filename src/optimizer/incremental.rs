@@ -0,0 +1,191 @@
+use crate::config::{CompressionConfig, ExplorationConfig};
+use crate::consts::LBF_SAMPLE_CONFIG;
+use crate::eval::lbf_evaluator::LBFEvaluator;
+use crate::eval::sample_eval::SampleEval;
+use crate::optimizer::explore_and_compress;
+use crate::optimizer::problem::{ItemFeatures, StripFeatures, StripProblem};
+use crate::optimizer::separator::Separator;
+use crate::quantify::allowed_bbox;
+use crate::sample::search::search_placement;
+use crate::sample::uniform_sampler::UniformBBoxSampler;
+use crate::util::listener::SolutionListener;
+use crate::util::terminator::Terminator;
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use jagua_rs::entities::Instance;
+use jagua_rs::probs::spp::entities::{SPInstance, SPPlacement, SPSolution};
+use log::{info, warn};
+use ordered_float::OrderedFloat;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::cmp::Reverse;
+use std::iter;
+
+/// Change in demand of the (original) items, as `(item_id, quantity)` pairs
+#[derive(Clone, Debug, Default)]
+pub struct DemandDelta {
+    pub added: Vec<(usize, usize)>,
+    pub removed: Vec<(usize, usize)>,
+}
+
+impl DemandDelta {
+    /// The instance with the demand of its items changed by the delta.
+    /// Mirrored twins follow the demand of their original item.
+    /// Fails if the delta refers to an unknown item or removes more copies of an item than demanded.
+    pub fn apply(&self, instance: &SPInstance, item_features: &ItemFeatures) -> Result<SPInstance> {
+        let mut items = instance.items.clone();
+        for &(id, _) in self.added.iter().chain(self.removed.iter()) {
+            if id >= items.len() || item_features.is_mirrored(id) {
+                bail!("item {} in the demand delta is not an (original) item of the instance", id);
+            }
+        }
+        for &(id, qty) in self.added.iter() {
+            items[id].1 += qty;
+        }
+        for &(id, qty) in self.removed.iter() {
+            if qty > items[id].1 {
+                bail!("cannot remove {} copies of item {}, only {} demanded", qty, id, items[id].1);
+            }
+            items[id].1 -= qty;
+        }
+        for id in 0..items.len() {
+            if item_features.is_mirrored(id) {
+                items[id].1 = items[item_features.original(id)].1;
+            }
+        }
+        Ok(SPInstance::new(items, instance.strip_height))
+    }
+}
+
+/// Re-optimizes an existing solution after the demand of some items changed, instead of starting from scratch.
+/// Removed items are dropped from the solution (the rightmost copies first) and added items are inserted at the best
+/// collision-free position within the strip, or a random one if there is none.
+/// The separator then resolves the overlaps, widening the strip where needed, within the time limit of the exploration.
+/// Afterwards, the strip is shrunk again by the exploration and compression phases, each with their full time limit.
+/// Returns the new instance along with the re-optimized solution.
+/// Fails if the delta cannot be applied to the instance, an added item does not fit in the strip,
+/// or the overlaps are not resolved in time.
+pub fn reoptimize(
+    instance: &SPInstance,
+    solution: &SPSolution,
+    delta: &DemandDelta,
+    strip_features: StripFeatures,
+    item_features: ItemFeatures,
+    mut rng: ChaCha20Rng,
+    sol_listener: &mut impl SolutionListener,
+    terminator: &mut (impl Terminator + Sync),
+    expl_config: &ExplorationConfig,
+    cmpr_config: &CompressionConfig,
+) -> Result<(SPInstance, SPSolution)> {
+    let mut next_rng = || ChaCha20Rng::seed_from_u64(rng.next_u64());
+    let new_instance = delta.apply(instance, &item_features)?;
+
+    let mut prob = StripProblem::new(new_instance.clone(), strip_features);
+    prob.item_features = item_features;
+    prob.change_strip_width(solution.strip_width());
+
+    //keep all placements, except for the rightmost (non-pinned) copies of the removed items
+    prob.place_pinned_items();
+    let mut placed = prob.features.without_pinned(
+        solution
+            .layout_snapshot
+            .placed_items
+            .values()
+            .sorted_by_key(|pi| Reverse(OrderedFloat(pi.shape.bbox.x_max)))
+            .map(|pi| SPPlacement {
+                item_id: pi.item_id,
+                d_transf: pi.d_transf,
+            })
+            .collect(),
+    );
+    for &(id, qty) in delta.removed.iter() {
+        let removals = placed
+            .iter()
+            .positions(|p| prob.item_features.original(p.item_id) == id)
+            .take(qty)
+            .collect_vec();
+        if removals.len() < qty {
+            warn!("[INCR] only {} of {} copies of item {} could be removed", removals.len(), qty, id);
+        }
+        for i in removals.into_iter().rev() {
+            placed.remove(i);
+        }
+    }
+    for p in placed {
+        prob.place_item(p);
+    }
+
+    //insert the missing copies of the items
+    let mut insert_rng = next_rng();
+    let missing = (0..new_instance.items.len())
+        .filter(|id| !prob.item_features.is_mirrored(*id))
+        .flat_map(|id| iter::repeat(id).take(prob.missing_qty(id)))
+        .collect_vec();
+    for &item_id in missing.iter() {
+        let item = new_instance.item(item_id);
+        let rot_intervals = prob.item_features.rot_intervals(item_id);
+        let region = prob.item_features.region(item_id);
+        let evaluator = LBFEvaluator::new(&prob.layout, item)
+            .with_item_holes(&prob.item_features.holes)
+            .with_region(region)
+            .with_orientation_costs(prob.item_features.orientation_costs(item_id));
+        let (best_sample, _) = search_placement(
+            &prob.layout,
+            item,
+            rot_intervals,
+            region,
+            None,
+            evaluator,
+            LBF_SAMPLE_CONFIG,
+            &mut insert_rng,
+        );
+        let d_transf = match best_sample {
+            Some((d_transf, SampleEval::Clear { .. })) => d_transf,
+            _ => {
+                //no room for the item, let the separator make room for it
+                let c_bbox = allowed_bbox(prob.layout.container.outer_cd.bbox, region);
+                match UniformBBoxSampler::new(c_bbox, item, c_bbox, rot_intervals) {
+                    Some(sampler) => sampler.sample(&mut insert_rng),
+                    None => bail!("item {} does not fit in the strip", item_id),
+                }
+            }
+        };
+        prob.place_item(SPPlacement { item_id, d_transf });
+    }
+    info!(
+        "[INCR] re-optimizing solution: removed {} and inserted {} items",
+        delta.removed.iter().map(|(_, qty)| qty).sum::<usize>(),
+        missing.len()
+    );
+
+    terminator.new_timeout(expl_config.time_limit);
+    let mut sep = Separator::new(new_instance.clone(), prob, next_rng(), expl_config.separator_config);
+
+    //resolve the overlaps, widening the strip until it succeeds
+    let mut resolved = false;
+    while !resolved && !terminator.kill() {
+        let (_, ct) = sep.separate(terminator, sol_listener);
+        resolved = ct.get_total_loss() == 0.0;
+        if !resolved {
+            let new_width = sep.prob.strip_width() * (1.0 + expl_config.shrink_step);
+            info!("[INCR] unable to resolve all overlaps, widening strip to {:.3}", new_width);
+            sep.change_strip_width(new_width, None);
+        }
+    }
+    if !resolved {
+        bail!("terminated before all overlaps of the re-optimized solution were resolved");
+    }
+
+    //the exploration gets its full time limit, regardless of the time spent widening
+    terminator.new_timeout(expl_config.time_limit);
+    let sol = explore_and_compress(
+        &new_instance,
+        sep,
+        next_rng(),
+        sol_listener,
+        terminator,
+        expl_config,
+        cmpr_config,
+    );
+    Ok((new_instance, sol))
+}
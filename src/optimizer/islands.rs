@@ -0,0 +1,174 @@
+use crate::{Duration, Instant};
+use crate::config::{ExplorationConfig, IslandConfig};
+use crate::consts::ISLAND_CONFIG_VARIATIONS;
+use crate::optimizer::explore::exploration_phase;
+use crate::optimizer::problem::SeparableProblem;
+use crate::optimizer::separator::Separator;
+use crate::util::listener::{DummySolListener, ReportType, SolutionListener};
+use crate::util::terminator::{ChildTerminator, Terminator};
+use itertools::Itertools;
+use jagua_rs::entities::Instance;
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use log::{error, info, warn};
+use ordered_float::OrderedFloat;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Configurations of the islands, derived from the base configuration.
+/// The first island uses the base configuration, the others cycle through variations of the shrink step and
+/// the number of iterations without improvement of the separator, so that not all islands explore the same way.
+pub fn island_configs(base: &ExplorationConfig, n_islands: usize) -> Vec<ExplorationConfig> {
+    (0..n_islands)
+        .map(|i| {
+            let (step_mult, iter_mult) = ISLAND_CONFIG_VARIATIONS[i % ISLAND_CONFIG_VARIATIONS.len()];
+            let mut config = *base;
+            config.shrink_step *= step_mult;
+            config.separator_config.iter_no_imprv_limit =
+                usize::max(1, (base.separator_config.iter_no_imprv_limit as f32 * iter_mult) as usize);
+            config
+        })
+        .collect()
+}
+
+/// Exploration phase with multiple independent separators (islands), each with its own seed and configuration.
+/// The islands explore in parallel for a number of epochs. After every epoch, the best feasible solution found by
+/// any island migrates to all islands which did not reach its width, so that it becomes the next target for all.
+/// The epochs run on a child terminator, the timeout of `term` marks the end of the whole phase.
+/// The separator is left at the best feasible solution. Returns all feasible solutions found, in order of decreasing width.
+pub fn island_exploration_phase(
+    instance: &SPInstance,
+    sep: &mut Separator,
+    sol_listener: &mut impl SolutionListener,
+    term: &(impl Terminator + Sync),
+    configs: &[ExplorationConfig],
+    island_config: &IslandConfig,
+) -> Vec<SPSolution> {
+    assert!(!configs.is_empty() && island_config.n_epochs > 0);
+    let mut feasible_solutions = vec![sep.prob.save()];
+    sol_listener.report(ReportType::ExplFeas, &feasible_solutions[0], instance);
+    info!(
+        "[ISLD] starting {} islands for {} epochs with initial width: {:.3}",
+        configs.len(),
+        island_config.n_epochs,
+        feasible_solutions[0].strip_width()
+    );
+
+    //the workers of the separator are shared over the islands
+    let mut islands = configs
+        .iter()
+        .map(|config| {
+            let mut sep_config = config.separator_config;
+            sep_config.n_workers = usize::max(1, sep_config.n_workers / configs.len());
+            Separator::new(
+                sep.instance.clone(),
+                sep.prob.clone(),
+                ChaCha20Rng::seed_from_u64(sep.rng.next_u64()),
+                sep_config,
+            )
+        })
+        .collect_vec();
+
+    //the phase lasts until the terminator times out, or the longest time limit of the islands otherwise
+    let phase_end = term
+        .timeout_at()
+        .unwrap_or_else(|| Instant::now() + configs.iter().map(|c| c.time_limit).max().unwrap());
+    let epoch_duration = phase_end.saturating_duration_since(Instant::now()) / island_config.n_epochs as u32;
+
+    for epoch in 0..island_config.n_epochs {
+        if term.kill() || Instant::now() >= phase_end {
+            break;
+        }
+        //every epoch ends at the latest when the phase does
+        let remaining = phase_end.saturating_duration_since(Instant::now());
+        let island_bests = explore_islands(instance, &mut islands, term, configs, epoch_duration.min(remaining));
+        for (i, best) in island_bests.iter().enumerate() {
+            match best {
+                Some(best) => info!("[ISLD] epoch {}: island {} reached width {:.3}", epoch, i, best.strip_width()),
+                None => warn!("[ISLD] epoch {}: island {} failed, it restarts from the best solution", epoch, i),
+            }
+        }
+
+        let epoch_best = island_bests
+            .iter()
+            .flatten()
+            .min_by_key(|sol| OrderedFloat(sol.strip_width()))
+            .filter(|sol| sol.strip_width() < feasible_solutions.last().unwrap().strip_width());
+        if let Some(epoch_best) = epoch_best {
+            info!(
+                "[ISLD] new best feasible solution after epoch {} (width: {:.3}, dens: {:.3}%)",
+                epoch,
+                epoch_best.strip_width(),
+                epoch_best.density(instance) * 100.0
+            );
+            feasible_solutions.push(epoch_best.clone());
+            sol_listener.report(ReportType::ExplFeas, epoch_best, instance);
+        }
+
+        //migration: every island continues from the best of its own and the overall best feasible solution
+        let best = feasible_solutions.last().unwrap();
+        for (island, island_best) in islands.iter_mut().zip(island_bests.iter()) {
+            let start = match island_best {
+                Some(island_best) if island_best.strip_width() <= best.strip_width() => island_best,
+                _ => best,
+            };
+            island.change_strip_width(start.strip_width(), None);
+            island.rollback(start, None);
+        }
+    }
+
+    let best = feasible_solutions.last().unwrap();
+    info!("[ISLD] finished, best feasible solution: width: {:.3} ({:.3}%)", best.strip_width(), best.density(instance) * 100.0);
+    sep.change_strip_width(best.strip_width(), None);
+    sep.rollback(best, None);
+
+    feasible_solutions
+}
+
+/// Runs the exploration phase on every island for the duration of an epoch, in parallel if possible.
+/// Returns the best feasible solution of every island, `None` if its thread panicked.
+fn explore_islands(
+    instance: &SPInstance,
+    islands: &mut [Separator],
+    term: &(impl Terminator + Sync),
+    configs: &[ExplorationConfig],
+    epoch_duration: Duration,
+) -> Vec<Option<SPSolution>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let island_bests = std::thread::scope(|s| {
+        let mut epoch_term = ChildTerminator::new(term);
+        epoch_term.new_timeout(epoch_duration);
+        let term = &epoch_term;
+        let handles = islands
+            .iter_mut()
+            .zip(configs.iter())
+            .map(|(island, config)| {
+                s.spawn(move || exploration_phase(instance, island, &mut DummySolListener, term, config).pop().unwrap())
+            })
+            .collect_vec();
+        handles
+            .into_iter()
+            .enumerate()
+            .map(|(i, h)| match h.join() {
+                Ok(best) => Some(best),
+                Err(e) => {
+                    error!("[ISLD] island {} panicked: {:?}", i, e);
+                    None
+                }
+            })
+            .collect_vec()
+    });
+
+    //without threads, the islands take turns within the epoch
+    #[cfg(target_arch = "wasm32")]
+    let island_bests = islands
+        .iter_mut()
+        .zip(configs.iter())
+        .map(|(island, config)| {
+            let mut island_term = ChildTerminator::new(term);
+            island_term.new_timeout(epoch_duration / configs.len() as u32);
+            exploration_phase(instance, island, &mut DummySolListener, &island_term, config).pop()
+        })
+        .collect_vec();
+
+    island_bests
+}
@@ -0,0 +1,179 @@
+use crate::FMT;
+use crate::config::ExplorationConfig;
+use crate::consts::{KNAPSACK_MAX_REMOVALS, LBF_SAMPLE_CONFIG};
+use crate::optimizer::lbf::LBFBuilder;
+use crate::optimizer::problem::{ItemFeatures, SeparableProblem, StripFeatures};
+use crate::optimizer::separator::Separator;
+use crate::quantify::allowed_bbox;
+use crate::sample::uniform_sampler::UniformBBoxSampler;
+use crate::util::listener::{ReportType, SolutionListener};
+use crate::util::terminator::Terminator;
+use itertools::Itertools;
+use jagua_rs::entities::{Instance, PItemKey};
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use log::info;
+use ordered_float::OrderedFloat;
+use rand::prelude::{IndexedRandom, IteratorRandom};
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::cmp::Reverse;
+use std::iter;
+
+/// Optimizes a knapsack variant of the strip packing problem: the strip has a fixed width and
+/// the goal is to maximize the total value of the placed items.
+/// `item_values` contains the value of a single copy of every item (mirrored twins included).
+pub fn optimize_knapsack(
+    instance: SPInstance,
+    strip_features: StripFeatures,
+    item_features: ItemFeatures,
+    item_values: &[f32],
+    strip_width: f32,
+    mut rng: ChaCha20Rng,
+    sol_listener: &mut impl SolutionListener,
+    terminator: &mut impl Terminator,
+    config: &ExplorationConfig,
+) -> SPSolution {
+    let mut next_rng = || ChaCha20Rng::seed_from_u64(rng.next_u64());
+
+    //construct an initial solution, prioritizing items with the highest value per unit of area
+    let item_order = (0..instance.items.len())
+        .filter(|id| !item_features.is_mirrored(*id))
+        .sorted_by_cached_key(|id| {
+            let convex_hull_area = instance.item(*id).shape_cd.surrogate().convex_hull_area;
+            Reverse(OrderedFloat(item_values[*id] / convex_hull_area))
+        })
+        .flat_map(|id| iter::repeat(id).take(instance.items[id].1))
+        .collect_vec();
+
+    let mut builder = LBFBuilder::new(instance.clone(), next_rng(), LBF_SAMPLE_CONFIG)
+        .with_strip_features(strip_features)
+        .with_item_features(item_features);
+    builder.prob.change_strip_width(strip_width);
+    let builder = builder.construct_fixed_width(&item_order);
+
+    terminator.new_timeout(config.time_limit);
+    let mut sep = Separator::new(builder.instance, builder.prob, next_rng(), config.separator_config);
+    let sol = knapsack_phase(&instance, item_values, &mut sep, sol_listener, terminator, config);
+
+    sol_listener.report(ReportType::Final, &sol, &instance);
+
+    sol
+}
+
+/// Improves the knapsack by repeatedly inserting a missing item (optionally removing some less valuable ones to
+/// make room for it) and trying to separate the resulting layout.
+/// Returns the most valuable feasible solution found.
+pub fn knapsack_phase(
+    instance: &SPInstance,
+    item_values: &[f32],
+    sep: &mut Separator,
+    sol_listener: &mut impl SolutionListener,
+    term: &impl Terminator,
+    config: &ExplorationConfig,
+) -> SPSolution {
+    let mut best = sep.prob.save();
+    let mut best_value = solution_value(&best, item_values);
+    let mut n_conseq_fails = 0;
+
+    //items which are larger than the container can never be inserted
+    let c_bbox = sep.prob.layout.container.outer_cd.bbox;
+    let samplers = (0..instance.items.len())
+        .map(|id| {
+            let bbox = allowed_bbox(c_bbox, sep.prob.item_features.region(id));
+            UniformBBoxSampler::new(bbox, instance.item(id), bbox, sep.prob.item_features.rot_intervals(id))
+        })
+        .collect_vec();
+
+    sol_listener.report(ReportType::ExplFeas, &best, instance);
+    info!(
+        "[KNAP] starting with value: {} ({} items, dens: {:.3}%)",
+        FMT().fmt2(best_value),
+        sep.prob.layout.placed_items.len(),
+        sep.prob.density() * 100.0
+    );
+
+    while !term.kill() {
+        //select a missing item to insert, more valuable items have more chance to be selected
+        let missing_ids = (0..instance.items.len())
+            .filter(|id| !sep.prob.item_features.is_mirrored(*id))
+            .filter(|id| sep.prob.missing_qty(*id) > 0 && samplers[*id].is_some())
+            .collect_vec();
+        if missing_ids.is_empty() {
+            info!("[KNAP] all items are placed, terminating");
+            break;
+        }
+        //fall back to a uniform choice if the values are not valid weights (e.g. all zero)
+        let insert_id = match missing_ids.choose_weighted(&mut sep.rng, |id| item_values[*id]) {
+            Ok(&id) => id,
+            Err(_) => *missing_ids.choose(&mut sep.rng).unwrap(),
+        };
+        let insert_value = item_values[insert_id];
+
+        //remove some less valuable items to make room for it
+        let n_removals = sep.rng.random_range(0..=KNAPSACK_MAX_REMOVALS);
+        let removals: Vec<PItemKey> = sep
+            .prob
+            .layout
+            .placed_items
+            .iter()
+            .filter(|(pk, pi)| item_values[pi.item_id] < insert_value && !sep.prob.is_pinned(*pk))
+            .map(|(pk, _)| pk)
+            .choose_multiple(&mut sep.rng, n_removals)
+            .into_iter()
+            .scan(0.0, |removed_value, pk| {
+                *removed_value += item_values[sep.prob.layout.placed_items[pk].item_id];
+                (*removed_value < insert_value).then_some(pk)
+            })
+            .collect();
+        for pk in removals.iter() {
+            sep.remove_item(*pk);
+        }
+
+        //insert the item at a random position in the container
+        let d_transf = samplers[insert_id].as_ref().unwrap().sample(&mut sep.rng);
+        sep.place_item(insert_id, d_transf);
+
+        let (local_best, ct) = sep.separate(term, sol_listener);
+        let value = solution_value(&local_best, item_values);
+
+        if ct.get_total_loss() == 0.0 && value > best_value {
+            info!(
+                "[KNAP] improved value: {} -> {} (inserted item {}, removed {} items)",
+                FMT().fmt2(best_value),
+                FMT().fmt2(value),
+                insert_id,
+                removals.len()
+            );
+            best = local_best;
+            best_value = value;
+            n_conseq_fails = 0;
+            sol_listener.report(ReportType::ExplFeas, &best, instance);
+        } else {
+            n_conseq_fails += 1;
+            sep.rollback(&best, None);
+
+            if n_conseq_fails >= config.max_conseq_failed_attempts.unwrap_or(usize::MAX) {
+                info!("[KNAP] max consecutive failed attempts ({}), terminating", n_conseq_fails);
+                break;
+            }
+        }
+    }
+
+    info!(
+        "[KNAP] finished, best value: {} ({} items, dens: {:.3}%)",
+        FMT().fmt2(best_value),
+        best.layout_snapshot.placed_items.len(),
+        best.density(instance) * 100.0
+    );
+
+    best
+}
+
+/// Total value of all items placed in the solution
+pub fn solution_value(sol: &SPSolution, item_values: &[f32]) -> f32 {
+    sol.layout_snapshot
+        .placed_items
+        .values()
+        .map(|pi| item_values[pi.item_id])
+        .sum()
+}
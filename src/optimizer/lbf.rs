@@ -1,28 +1,40 @@
 use crate::Instant;
 use crate::eval::lbf_evaluator::LBFEvaluator;
-use crate::eval::sample_eval::SampleEval;
+use crate::eval::sample_eval::{SampleEval, SampleEvaluator};
+use crate::optimizer::problem::{ItemFeatures, StripFeatures, StripProblem};
+use crate::quantify::tracker::CollisionTracker;
+use crate::quantify::nesting::holes_of;
 use crate::sample::search::{SampleConfig, search_placement};
+use crate::sample::uniform_sampler::UniformBBoxSampler;
 use crate::util::assertions;
 use itertools::Itertools;
 use jagua_rs::entities::Instance;
-use jagua_rs::probs::spp::entities::{SPInstance, SPPlacement, SPProblem};
-use log::debug;
+use jagua_rs::geometry::geo_traits::Transformable;
+use jagua_rs::probs::spp::entities::{SPInstance, SPPlacement};
+use log::{debug, info, warn};
 use ordered_float::OrderedFloat;
 use rand::prelude::SmallRng;
 use rand_chacha::ChaCha20Rng;
 use std::cmp::Reverse;
 use std::iter;
 
+/// A previous solution to start the optimization from, instead of constructing a new one
+#[derive(Clone, Debug)]
+pub struct InitSolution {
+    pub strip_width: f32,
+    pub placements: Vec<SPPlacement>,
+}
+
 pub struct LBFBuilder {
     pub instance: SPInstance,
-    pub prob: SPProblem,
+    pub prob: StripProblem,
     pub rng: ChaCha20Rng,
     pub sample_config: SampleConfig,
 }
 
 impl LBFBuilder {
     pub fn new(instance: SPInstance, rng: ChaCha20Rng, sample_config: SampleConfig) -> Self {
-        let prob = SPProblem::new(instance.clone());
+        let prob = StripProblem::new(instance.clone(), StripFeatures::default());
 
         Self {
             instance,
@@ -32,10 +44,94 @@ impl LBFBuilder {
         }
     }
 
+    /// Applies the features (e.g. defects) to the strip, must be called before any item is placed.
+    pub fn with_strip_features(mut self, features: StripFeatures) -> Self {
+        debug_assert!(self.prob.layout.placed_items.is_empty());
+        self.prob = StripProblem::new(self.instance.clone(), features);
+        self
+    }
+
+    /// Sets the features of the items (e.g. mirrored twins), must be called before any item is placed.
+    pub fn with_item_features(mut self, item_features: ItemFeatures) -> Self {
+        debug_assert!(self.prob.layout.placed_items.is_empty());
+        self.prob.item_features = item_features;
+        self
+    }
+
     pub fn construct(mut self) -> Self {
         let start = Instant::now();
+        self.prob.place_pinned_items();
+        let sorted_item_indices = self.missing_items();
+
+        debug!("[CONSTR] placing items in order: {:?}", sorted_item_indices);
+
+        for item_id in sorted_item_indices {
+            self.place_item(item_id);
+        }
+
+        self.prob.fit_strip();
+        debug!(
+            "[CONSTR] placed all items in width: {:.3} (in {:?})",
+            self.prob.strip_width(),
+            start.elapsed()
+        );
+        self
+    }
+
+    /// Restores a previous solution instead of constructing one from scratch.
+    /// Items missing from the solution are added as they would be during construction.
+    /// Falls back to a regular construction if the solution is not feasible for the instance.
+    pub fn restore(mut self, init: &InitSolution) -> Self {
+        let start = Instant::now();
+        let mut prob = self.prob.clone();
+        prob.change_strip_width(init.strip_width);
+        prob.place_pinned_items();
+
+        //the pinned items are part of the solution as well
+        let placements = prob.features.without_pinned(init.placements.clone());
+
+        for p in placements {
+            match prob.missing_qty(prob.item_features.original(p.item_id)) {
+                0 => warn!("[CONSTR] more copies of item {} in the initial solution than demanded, skipping", p.item_id),
+                _ => {
+                    prob.place_item(p);
+                }
+            }
+        }
+
+        let ct = CollisionTracker::new(
+            &prob.layout,
+            prob.item_features.holes.clone(),
+            prob.item_features.regions.clone(),
+        );
+        if ct.get_total_loss() > 0.0 {
+            warn!("[CONSTR] initial solution is not feasible, constructing a new one instead");
+            return self.construct();
+        }
+        self.prob = prob;
+
+        let missing_items = self.missing_items();
+        if !missing_items.is_empty() {
+            info!("[CONSTR] adding {} items missing from the initial solution", missing_items.len());
+            for item_id in missing_items {
+                self.place_item(item_id);
+            }
+            self.prob.fit_strip();
+        }
+        info!(
+            "[CONSTR] restored initial solution in width: {:.3} (in {:?})",
+            self.prob.strip_width(),
+            start.elapsed()
+        );
+        self
+    }
+
+    /// All copies of the items which are not placed yet, largest items first
+    pub fn missing_items(&self) -> Vec<usize> {
         let n_items = self.instance.items.len();
-        let sorted_item_indices = (0..n_items)
+        //mirrored twins are never placed directly, only as a variant of their original item
+        (0..n_items)
+            .filter(|id| !self.prob.item_features.is_mirrored(*id))
             .sorted_by_cached_key(|id| {
                 let item_shape = self.instance.item(*id).shape_cd.as_ref();
                 let convex_hull_area = item_shape.surrogate().convex_hull_area;
@@ -43,22 +139,47 @@ impl LBFBuilder {
                 Reverse(OrderedFloat(convex_hull_area * diameter))
             })
             .map(|id| {
-                let missing_qty = self.prob.item_demand_qtys[id];
+                let missing_qty = self.prob.missing_qty(id);
                 iter::repeat(id).take(missing_qty)
             })
             .flatten()
-            .collect_vec();
+            .collect_vec()
+    }
 
-        debug!("[CONSTR] placing items in order: {:?}", sorted_item_indices);
+    /// Constructs a solution within the current strip width, placing the items in the given order.
+    /// Items for which no collision-free placement exists are skipped instead of expanding the strip.
+    pub fn construct_fixed_width(mut self, item_order: &[usize]) -> Self {
+        self.prob.place_pinned_items();
+        self.insert_items(item_order)
+    }
 
-        for item_id in sorted_item_indices {
-            self.place_item(item_id);
+    /// Inserts the items in the given order alongside the already placed items, within the current strip width.
+    /// Items for which no collision-free placement exists are skipped.
+    pub fn insert_items(mut self, item_order: &[usize]) -> Self {
+        let start = Instant::now();
+        let mut n_skipped = 0;
+
+        for &item_id in item_order {
+            if self.prob.missing_qty(item_id) == 0 {
+                //already placed (e.g. as a pinned item)
+                continue;
+            }
+            match self.find_placement(item_id) {
+                Some(p_opt) => {
+                    self.prob.place_item(p_opt);
+                }
+                None => {
+                    debug!("[CONSTR] no room for item with id {}, skipping", item_id);
+                    n_skipped += 1;
+                }
+            }
         }
 
-        self.prob.fit_strip();
         debug!(
-            "[CONSTR] placed all items in width: {:.3} (in {:?})",
+            "[CONSTR] placed {} items in fixed width: {:.3}, skipped {} (in {:?})",
+            self.prob.layout.placed_items.len(),
             self.prob.strip_width(),
+            n_skipped,
             start.elapsed()
         );
         self
@@ -92,14 +213,38 @@ impl LBFBuilder {
         }
     }
 
+    /// Searches a collision-free placement for the item, in either of its variants if it can be mirrored.
     fn find_placement(&mut self, item_id: usize) -> Option<SPPlacement> {
+        let variants = iter::once(item_id)
+            .chain(self.prob.item_features.mirror_twin(item_id))
+            .collect_vec();
+
+        variants
+            .into_iter()
+            .filter_map(|id| self.find_variant_placement(id))
+            .min_by_key(|(_, eval)| *eval)
+            .map(|(p_opt, _)| p_opt)
+    }
+
+    fn find_variant_placement(&mut self, item_id: usize) -> Option<(SPPlacement, SampleEval)> {
+        //small items are placed in the holes of already placed items whenever possible
+        if let Some(p_opt) = self.find_hole_placement(item_id) {
+            return Some(p_opt);
+        }
+
         let layout = &self.prob.layout;
         let item = self.instance.item(item_id);
-        let evaluator = LBFEvaluator::new(layout, item);
+        let region = self.prob.item_features.region(item_id);
+        let evaluator = LBFEvaluator::new(layout, item)
+            .with_item_holes(&self.prob.item_features.holes)
+            .with_region(region)
+            .with_orientation_costs(self.prob.item_features.orientation_costs(item_id));
 
         let (best_sample, _) = search_placement(
             layout,
             item,
+            self.prob.item_features.rot_intervals(item_id),
+            region,
             None,
             evaluator,
             self.sample_config,
@@ -107,8 +252,47 @@ impl LBFBuilder {
         );
 
         match best_sample {
-            Some((d_transf, SampleEval::Clear { .. })) => Some(SPPlacement { item_id, d_transf }),
+            Some((d_transf, eval @ SampleEval::Clear { .. })) => Some((SPPlacement { item_id, d_transf }, eval)),
             _ => None,
         }
     }
+
+    /// Searches a collision-free placement for the item within the holes of the placed items.
+    fn find_hole_placement(&mut self, item_id: usize) -> Option<(SPPlacement, SampleEval)> {
+        let item_holes = &self.prob.item_features.holes;
+        if item_holes.is_empty() {
+            return None;
+        }
+        let layout = &self.prob.layout;
+        let item = self.instance.item(item_id);
+
+        //bounding boxes of all holes in the layout which are large enough to contain the item
+        let hole_bboxes = layout
+            .placed_items
+            .values()
+            .flat_map(|pi| {
+                let t = pi.d_transf.compose();
+                holes_of(item_holes, pi.item_id).iter().map(move |h| h.transform_clone(&t))
+            })
+            .filter(|h| h.area > item.shape_cd.area)
+            .map(|h| h.bbox)
+            .collect_vec();
+
+        let mut evaluator = LBFEvaluator::new(layout, item)
+            .with_item_holes(item_holes)
+            .with_region(self.prob.item_features.region(item_id))
+            .with_orientation_costs(self.prob.item_features.orientation_costs(item_id));
+        hole_bboxes
+            .into_iter()
+            .filter_map(|bbox| UniformBBoxSampler::new(bbox, item, bbox, self.prob.item_features.rot_intervals(item_id)))
+            .flat_map(|sampler| {
+                (0..self.sample_config.n_container_samples)
+                    .map(|_| sampler.sample(&mut self.rng))
+                    .collect_vec()
+            })
+            .map(|d_transf| (d_transf, evaluator.eval(d_transf, None)))
+            .filter(|(_, eval)| matches!(eval, SampleEval::Clear { .. }))
+            .min_by_key(|(_, eval)| *eval)
+            .map(|(d_transf, eval)| (SPPlacement { item_id, d_transf }, eval))
+    }
 }
@@ -0,0 +1,67 @@
+use itertools::Itertools;
+use log::info;
+
+/// Order of a single garment size
+#[derive(Debug, Clone)]
+pub struct SizeOrder {
+    pub name: String,
+    /// Number of garments to cut in this size
+    pub order_qty: usize,
+}
+
+/// A marker: the number of garments of every size nested together in a single layout,
+/// which is cut from a stack of plies of fabric at once
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Marker {
+    /// Number of garments per size in the marker (indexed like the sizes of the order)
+    pub ratios: Vec<usize>,
+    /// Number of plies the marker is cut from
+    pub plies: usize,
+}
+
+impl Marker {
+    pub fn n_garments(&self) -> usize {
+        self.ratios.iter().sum()
+    }
+}
+
+/// Splits the order into markers, such that every size is cut exactly its ordered quantity.
+/// Every marker is spread on as many plies as possible (at most `max_plies`), the ratios are then the number
+/// of garments of every size which can be cut for all of those plies.
+/// If `max_garments` is given, the ratios of the largest sizes are decremented until the marker fits within it.
+///
+/// The plan is a fixed greedy heuristic: it only minimizes the number of markers and never compares alternative
+/// ratios by the length of their nested layouts, so it is not guaranteed to minimize the total fabric length.
+pub fn plan_markers(sizes: &[SizeOrder], max_plies: usize, max_garments: Option<usize>) -> Vec<Marker> {
+    assert!(max_plies > 0 && max_garments != Some(0));
+    let mut remaining = sizes.iter().map(|s| s.order_qty).collect_vec();
+    let mut markers = vec![];
+
+    while remaining.iter().any(|q| *q > 0) {
+        let mut plies = max_plies.min(*remaining.iter().max().unwrap());
+        let mut ratios = remaining.iter().map(|q| q / plies).collect_vec();
+        if ratios.iter().all(|r| *r == 0) {
+            //fewer garments remain of every size than plies: one garment of every remaining size per ply
+            plies = remaining.iter().copied().filter(|q| *q > 0).min().unwrap();
+            ratios = remaining.iter().map(|q| (*q > 0) as usize).collect();
+        }
+        if let Some(max_garments) = max_garments {
+            while ratios.iter().sum::<usize>() > max_garments {
+                let (i, _) = ratios.iter().enumerate().max_by_key(|(_, r)| **r).unwrap();
+                ratios[i] -= 1;
+            }
+        }
+        for (q, r) in remaining.iter_mut().zip(ratios.iter()) {
+            *q -= r * plies;
+        }
+        let marker = Marker { ratios, plies };
+        info!(
+            "[MARKER] planned marker with ratios {:?} on {} plies ({} garments)",
+            marker.ratios,
+            marker.plies,
+            marker.n_garments() * marker.plies
+        );
+        markers.push(marker);
+    }
+    markers
+}
@@ -3,7 +3,9 @@ use crate::config::*;
 use crate::consts::LBF_SAMPLE_CONFIG;
 use crate::optimizer::compress::compression_phase;
 use crate::optimizer::explore::exploration_phase;
-use crate::optimizer::lbf::LBFBuilder;
+use crate::optimizer::islands::{island_configs, island_exploration_phase};
+use crate::optimizer::lbf::{InitSolution, LBFBuilder};
+use crate::optimizer::problem::{ItemFeatures, StripFeatures};
 use crate::optimizer::separator::Separator;
 use crate::util::listener::{ReportType, SolutionListener};
 use crate::util::terminator::Terminator;
@@ -13,48 +15,101 @@ use rand::{RngCore, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 use std::time::Duration;
 
+pub mod bpp;
 pub mod compress;
+pub mod disrupt;
 pub mod explore;
+pub mod fill;
+pub mod fold;
+pub mod incremental;
+pub mod islands;
+pub mod knapsack;
 pub mod lbf;
+pub mod marker;
+pub mod modes;
+pub mod open_dim;
+pub mod problem;
+pub mod rolls;
 pub mod separator;
 mod worker;
 
 pub fn optimize(
     instance: SPInstance,
+    strip_features: StripFeatures,
+    item_features: ItemFeatures,
     mut rng: ChaCha20Rng,
     sol_listener: &mut impl SolutionListener,
-    terminator: &mut impl Terminator,
+    terminator: &mut (impl Terminator + Sync),
     expl_config: &ExplorationConfig,
     cmpr_config: &CompressionConfig,
+    init_solution: Option<&InitSolution>,
 ) -> SPSolution {
     let mut next_rng = || ChaCha20Rng::seed_from_u64(rng.next_u64());
-    let builder = LBFBuilder::new(instance.clone(), next_rng(), LBF_SAMPLE_CONFIG).construct();
+    let builder = LBFBuilder::new(instance.clone(), next_rng(), LBF_SAMPLE_CONFIG)
+        .with_strip_features(strip_features)
+        .with_item_features(item_features);
+    //warm start from a previous solution if one is provided
+    let builder = match init_solution {
+        Some(init) => builder.restore(init),
+        None => builder.construct(),
+    };
 
     terminator.new_timeout(expl_config.time_limit);
-    let mut expl_separator = Separator::new(
+    let expl_separator = Separator::new(
         builder.instance,
         builder.prob,
         next_rng(),
         expl_config.separator_config,
     );
-    let solutions = exploration_phase(
+    explore_and_compress(
         &instance,
-        &mut expl_separator,
+        expl_separator,
+        next_rng(),
         sol_listener,
         terminator,
         expl_config,
-    );
+        cmpr_config,
+    )
+}
+
+/// Runs the exploration phase (its timeout should already be set) followed by the compression phase
+fn explore_and_compress(
+    instance: &SPInstance,
+    mut expl_separator: Separator,
+    cmpr_rng: ChaCha20Rng,
+    sol_listener: &mut impl SolutionListener,
+    terminator: &mut (impl Terminator + Sync),
+    expl_config: &ExplorationConfig,
+    cmpr_config: &CompressionConfig,
+) -> SPSolution {
+    let solutions = match &expl_config.islands {
+        Some(island_config) => island_exploration_phase(
+            instance,
+            &mut expl_separator,
+            sol_listener,
+            terminator,
+            &island_configs(expl_config, island_config.n_islands),
+            island_config,
+        ),
+        None => exploration_phase(
+            instance,
+            &mut expl_separator,
+            sol_listener,
+            terminator,
+            expl_config,
+        ),
+    };
     let final_explore_sol = solutions.last().unwrap().clone();
 
     terminator.new_timeout(cmpr_config.time_limit);
     let mut cmpr_separator = Separator::new(
         expl_separator.instance,
         expl_separator.prob,
-        next_rng(),
+        cmpr_rng,
         cmpr_config.separator_config,
     );
     let cmpr_sol = compression_phase(
-        &instance,
+        instance,
         &mut cmpr_separator,
         &final_explore_sol,
         sol_listener,
@@ -62,7 +117,7 @@ pub fn optimize(
         cmpr_config,
     );
 
-    sol_listener.report(ReportType::Final, &cmpr_sol, &instance);
+    sol_listener.report(ReportType::Final, &cmpr_sol, instance);
 
     cmpr_sol
 }
@@ -0,0 +1,586 @@
+use crate::config::{OpenDimObjective, SparrowConfig};
+use crate::optimizer::bpp::optimize_bpp;
+use crate::optimizer::fill::fill_strip;
+use crate::optimizer::fold::unfold_solution;
+use crate::optimizer::knapsack::{optimize_knapsack, solution_value};
+use crate::optimizer::lbf::InitSolution;
+use crate::optimizer::marker::{SizeOrder, plan_markers};
+use crate::optimizer::open_dim::optimize_open_dimension;
+use crate::optimizer::optimize;
+use crate::optimizer::problem::{ItemFeatures, StripFeatures};
+use crate::optimizer::rolls::{Roll, optimize_roll_selection};
+use crate::util::clearance::verify_clearances;
+use crate::util::io;
+use crate::util::io::{
+    BPOutput, ExportedSolution, ExtContainerSize, ExtItemFeatures, ExtItemQuantity, ExtKPInstance, ExtMarker,
+    ExtMarkerOrder, ExtRollCatalogue, ExtRollSummary, ExtRunSummary, ExtSizeRatio, ExtStripFeatures, ExtUnplacedItem,
+    KPOutput, MPOutput, RSOutput, SPOutput,
+};
+use crate::util::listener::{ProgressListener, ReportType, SolutionListener};
+use crate::util::terminator::Terminator;
+use crate::EPOCH;
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use jagua_rs::entities::Instance;
+use jagua_rs::io::import::Importer;
+use jagua_rs::probs::bpp::entities::{BPInstance, BPSolution};
+use jagua_rs::probs::bpp::io::ext_repr::ExtBPInstance;
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use jagua_rs::probs::spp::io::ext_repr::ExtSPInstance;
+use log::{error, info};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Solves a (folded) strip packing problem, optionally warm started from a previous output
+/// and with multiple runs in parallel if `n_runs > 1`.
+/// The free space of the final strip is filled with the optional copies of the items, if any.
+pub fn solve_strip_packing(
+    ext_instance: ExtSPInstance,
+    ext_item_features: ExtItemFeatures,
+    ext_strip_features: ExtStripFeatures,
+    init_output: Option<&SPOutput>,
+    folded: bool,
+    n_runs: usize,
+    config: &SparrowConfig,
+    mut rng: ChaCha20Rng,
+    sol_listener: &mut impl SolutionListener,
+    terminator: &mut (impl Terminator + Clone + Send + Sync),
+) -> Result<SPOutput> {
+    //on a folded strip, only one half of the strip is optimized
+    let (opt_ext_instance, ext_item_features) = match folded {
+        true => {
+            if !ext_strip_features.defects.is_empty()
+                || !ext_strip_features.quality_zones.is_empty()
+                || !ext_strip_features.pinned_items.is_empty()
+            {
+                bail!("defects, quality zones and pinned items are not supported on a folded strip");
+            }
+            io::fold_instance(&ext_instance, &ext_item_features)
+        }
+        false => (ext_instance.clone(), ext_item_features),
+    };
+
+    let (instance, item_features, strip_features) =
+        io::import_spp_problem(config, &opt_ext_instance, &ext_item_features, &ext_strip_features)?;
+
+    info!(
+        "[MODE] loaded instance {} with #{} items and {} defects",
+        ext_instance.name,
+        instance.total_item_qty(),
+        strip_features.defects.len()
+    );
+
+    let init_solution = match init_output {
+        Some(output) => {
+            let init_solution = io::import_init_solution(&instance, &item_features, output)?;
+            info!(
+                "[MODE] warm starting from a solution with #{} items in width {:.3}",
+                init_solution.placements.len(),
+                init_solution.strip_width
+            );
+            Some(init_solution)
+        }
+        None => None,
+    };
+
+    let fill_rng = ChaCha20Rng::seed_from_u64(rng.next_u64());
+    let (solution, runs) = match n_runs {
+        1 => {
+            let solution = optimize(
+                instance.clone(),
+                strip_features.clone(),
+                item_features.clone(),
+                rng,
+                sol_listener,
+                terminator,
+                &config.expl_cfg,
+                &config.cmpr_cfg,
+                init_solution.as_ref(),
+            );
+            (solution, vec![])
+        }
+        _ => optimize_portfolio(
+            n_runs,
+            &instance,
+            &strip_features,
+            &item_features,
+            rng,
+            sol_listener,
+            terminator,
+            config,
+            init_solution.as_ref(),
+        )?,
+    };
+
+    //fill up the free space in the strip with the optional copies of the items
+    let (fill_instance, solution) = match item_features.extra_qtys.is_empty() {
+        true => (instance.clone(), solution),
+        false => {
+            let (fill_instance, solution, _) = fill_strip(
+                &instance,
+                &solution,
+                strip_features,
+                item_features.clone(),
+                fill_rng,
+                sol_listener,
+            )?;
+            (fill_instance, solution)
+        }
+    };
+    let extra_items = io::extra_items(&instance, &solution, &item_features);
+
+    //both halves of a folded strip are exported, every placed item yields two copies
+    let (fill_instance, solution, extra_items) = match folded {
+        true => {
+            let (unfolded_instance, unfolded_solution) = unfold_solution(&fill_instance, &solution, &item_features);
+            sol_listener.report(ReportType::Final, &unfolded_solution, &unfolded_instance);
+            let extra_items = extra_items
+                .into_iter()
+                .map(|e| ExtItemQuantity {
+                    quantity: 2 * e.quantity,
+                    ..e
+                })
+                .collect();
+            (unfolded_instance, unfolded_solution, extra_items)
+        }
+        false => (fill_instance, solution, extra_items),
+    };
+
+    let exported = ExportedSolution::new(&fill_instance, &solution, &item_features, &ext_strip_features);
+    Ok(SPOutput {
+        extra_items,
+        runs,
+        ..exported.into_output(ext_instance, ext_strip_features)
+    })
+}
+
+/// Launches independent optimizations with different seeds in parallel, the physical cores are divided among them.
+/// Every run gets its own clone of the terminator.
+/// The progress of every run is logged, only the best final solution is reported to the listener.
+/// Runs which panic are skipped, fails only if all runs do.
+pub fn optimize_portfolio(
+    n_runs: usize,
+    instance: &SPInstance,
+    strip_features: &StripFeatures,
+    item_features: &ItemFeatures,
+    mut rng: ChaCha20Rng,
+    sol_listener: &mut impl SolutionListener,
+    terminator: &(impl Terminator + Clone + Send + Sync),
+    config: &SparrowConfig,
+    init_solution: Option<&InitSolution>,
+) -> Result<(SPSolution, Vec<ExtRunSummary>)> {
+    let n_workers = usize::max(1, num_cpus::get_physical() / n_runs);
+    let mut run_config = *config;
+    run_config.expl_cfg.separator_config.n_workers = n_workers;
+    run_config.cmpr_cfg.separator_config.n_workers = n_workers;
+    info!("[MODE] starting {} runs with {} workers each", n_runs, n_workers);
+
+    let seeds = (0..n_runs).map(|_| rng.next_u64()).collect::<Vec<_>>();
+    let solutions = std::thread::scope(|s| {
+        let handles = seeds
+            .iter()
+            .enumerate()
+            .map(|(run, seed)| {
+                let mut terminator = terminator.clone();
+                let run_config = &run_config;
+                s.spawn(move || {
+                    optimize(
+                        instance.clone(),
+                        strip_features.clone(),
+                        item_features.clone(),
+                        ChaCha20Rng::seed_from_u64(*seed),
+                        &mut ProgressListener { run },
+                        &mut terminator,
+                        &run_config.expl_cfg,
+                        &run_config.cmpr_cfg,
+                        init_solution,
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .enumerate()
+            .filter_map(|(run, h)| match h.join() {
+                Ok(solution) => Some((run, solution)),
+                Err(e) => {
+                    error!("[MODE] run {} (seed: {}) panicked, skipping it: {:?}", run, seeds[run], e);
+                    None
+                }
+            })
+            .collect::<Vec<_>>()
+    });
+    if solutions.is_empty() {
+        bail!("all runs failed");
+    }
+
+    let summaries = solutions
+        .iter()
+        .map(|(run, solution)| ExtRunSummary {
+            run: *run,
+            seed: seeds[*run],
+            strip_width: solution.strip_width(),
+            density: solution.density(instance),
+        })
+        .collect::<Vec<_>>();
+    for summary in summaries.iter() {
+        info!(
+            "[MODE] run {} (seed: {}): width: {:.3} ({:.3}%)",
+            summary.run,
+            summary.seed,
+            summary.strip_width,
+            summary.density * 100.0
+        );
+    }
+
+    let (best_run, best_solution) = solutions
+        .into_iter()
+        .min_by(|(_, a), (_, b)| a.strip_width().total_cmp(&b.strip_width()))
+        .unwrap();
+    info!("[MODE] best solution found by run {}", best_run);
+    sol_listener.report(ReportType::Final, &best_solution, instance);
+
+    Ok((best_solution, summaries))
+}
+
+/// Solves an open dimension problem, in which both the width and height of the container are minimized
+pub fn solve_open_dimension(
+    ext_instance: ExtSPInstance,
+    ext_item_features: ExtItemFeatures,
+    ext_strip_features: ExtStripFeatures,
+    objective: OpenDimObjective,
+    config: &SparrowConfig,
+    rng: ChaCha20Rng,
+    sol_listener: &mut impl SolutionListener,
+    terminator: &mut impl Terminator,
+) -> Result<SPOutput> {
+    let (instance, item_features, strip_features) =
+        io::import_spp_problem(config, &ext_instance, &ext_item_features, &ext_strip_features)?;
+
+    info!(
+        "[MODE] loaded open dimension instance {} with #{} items, minimizing {:?}",
+        ext_instance.name,
+        instance.total_item_qty(),
+        objective
+    );
+
+    let solution = optimize_open_dimension(
+        instance.clone(),
+        strip_features,
+        item_features.clone(),
+        rng,
+        sol_listener,
+        terminator,
+        &config.expl_cfg,
+        &config.cmpr_cfg,
+        objective,
+    );
+
+    let exported = ExportedSolution::new(&instance, &solution, &item_features, &ext_strip_features);
+    Ok(SPOutput {
+        container_size: Some(ExtContainerSize {
+            width: solution.strip_width(),
+            height: solution.strip.fixed_height,
+        }),
+        ..exported.into_output(ext_instance, ext_strip_features)
+    })
+}
+
+/// Selects the cheapest roll of the catalogue to nest the strip packing instance on
+pub fn solve_roll_selection(
+    mut ext_instance: ExtSPInstance,
+    ext_item_features: ExtItemFeatures,
+    ext_strip_features: ExtStripFeatures,
+    ext_catalogue: &ExtRollCatalogue,
+    config: &SparrowConfig,
+    rng: ChaCha20Rng,
+    sol_listener: &mut impl SolutionListener,
+    terminator: &mut impl Terminator,
+) -> Result<RSOutput> {
+    //the instance is imported on the highest roll, the border margin should at least fit on it
+    //(lower rolls on which the items do not fit are dropped during the selection)
+    ext_instance.strip_height = ext_catalogue.rolls.iter().map(|r| r.height).fold(0.0, f32::max);
+    let (instance, item_features, strip_features) =
+        io::import_spp_problem(config, &ext_instance, &ext_item_features, &ext_strip_features)?;
+    let rolls = ext_catalogue
+        .rolls
+        .iter()
+        .map(|r| Roll {
+            height: r.height,
+            cost: r.cost,
+        })
+        .collect::<Vec<_>>();
+
+    info!(
+        "[MODE] loaded instance {} with #{} items and a catalogue of {} rolls",
+        ext_instance.name,
+        instance.total_item_qty(),
+        rolls.len()
+    );
+
+    let Some((selected, solution, summaries)) = optimize_roll_selection(
+        instance.clone(),
+        strip_features,
+        item_features.clone(),
+        &rolls,
+        rng,
+        sol_listener,
+        terminator,
+        &config.expl_cfg,
+        &config.cmpr_cfg,
+    ) else {
+        bail!("the items do not fit on any of the rolls in the catalogue");
+    };
+
+    let roll_summary = summaries
+        .iter()
+        .zip(ext_catalogue.rolls.iter())
+        .enumerate()
+        .map(|(i, (summary, ext_roll))| ExtRollSummary {
+            roll: *ext_roll,
+            selected: i == selected,
+            strip_width: summary.strip_width,
+            total_cost: summary.total_cost(),
+            dropped_in_round: summary.dropped_in_round,
+        })
+        .collect();
+
+    //the solution is expressed on the selected roll
+    ext_instance.strip_height = rolls[selected].height;
+
+    let exported = ExportedSolution::new(&instance, &solution, &item_features, &ext_strip_features);
+    Ok(RSOutput {
+        output: exported.into_output(ext_instance, ext_strip_features),
+        roll_summary,
+    })
+}
+
+/// Plans the markers of a garment order and nests every distinct marker.
+/// `marker_listener` provides the listener of every distinct marker, by its index.
+pub fn solve_marker_planning<L: SolutionListener>(
+    ext_instance: ExtSPInstance,
+    ext_item_features: ExtItemFeatures,
+    ext_strip_features: ExtStripFeatures,
+    ext_order: &ExtMarkerOrder,
+    config: &SparrowConfig,
+    mut rng: ChaCha20Rng,
+    mut marker_listener: impl FnMut(usize) -> L,
+    terminator: &mut (impl Terminator + Sync),
+) -> Result<MPOutput> {
+    if ext_order.max_plies == 0 || ext_order.max_garments_per_marker == Some(0) {
+        bail!("the maximum number of plies and garments per marker should be positive");
+    }
+    if !ext_strip_features.pinned_items.is_empty() {
+        bail!("pinned items are not supported in marker planning");
+    }
+
+    let sizes = ext_order
+        .sizes
+        .iter()
+        .map(|s| SizeOrder {
+            name: s.name.clone(),
+            order_qty: s.order_qty,
+        })
+        .collect::<Vec<_>>();
+    let markers = plan_markers(&sizes, ext_order.max_plies, ext_order.max_garments_per_marker);
+
+    //markers with the same ratios share a single layout, the time budget is split over the distinct ones
+    let distinct_ratios = markers.iter().map(|m| m.ratios.clone()).unique().collect::<Vec<_>>();
+    info!(
+        "[MODE] loaded marker order {} with {} sizes, planned {} markers ({} distinct)",
+        ext_instance.name,
+        sizes.len(),
+        markers.len(),
+        distinct_ratios.len()
+    );
+    let mut expl_cfg = config.expl_cfg;
+    expl_cfg.time_limit /= distinct_ratios.len().max(1) as u32;
+    let mut cmpr_cfg = config.cmpr_cfg;
+    cmpr_cfg.time_limit /= distinct_ratios.len().max(1) as u32;
+
+    let mut layouts = vec![];
+    for (i, ratios) in distinct_ratios.iter().enumerate() {
+        let (marker_ext_instance, marker_ext_features) =
+            io::marker_instance(&ext_instance, &ext_item_features, &ext_order.sizes, ratios)?;
+        let (instance, item_features, strip_features) =
+            io::import_spp_problem(config, &marker_ext_instance, &marker_ext_features, &ext_strip_features)?;
+        info!("[MODE] nesting marker {} with ratios {:?} (#{} items)", i, ratios, instance.total_item_qty());
+
+        let solution = optimize(
+            instance.clone(),
+            strip_features,
+            item_features.clone(),
+            ChaCha20Rng::seed_from_u64(rng.next_u64()),
+            &mut marker_listener(i),
+            terminator,
+            &expl_cfg,
+            &cmpr_cfg,
+            None,
+        );
+
+        let exported = ExportedSolution::new(&instance, &solution, &item_features, &ext_strip_features);
+        layouts.push((solution.strip_width(), exported));
+    }
+
+    let ext_markers = markers
+        .iter()
+        .map(|marker| {
+            let i = distinct_ratios.iter().position(|r| *r == marker.ratios).unwrap();
+            let (length, exported) = &layouts[i];
+            ExtMarker {
+                ratios: sizes
+                    .iter()
+                    .zip(marker.ratios.iter())
+                    .map(|(size, ratio)| ExtSizeRatio {
+                        size: size.name.clone(),
+                        ratio: *ratio,
+                    })
+                    .collect(),
+                plies: marker.plies,
+                length: *length,
+                solution: exported.solution.clone(),
+                mirrored_items: exported.mirrored_items.clone(),
+                clearance_violations: exported.clearance_violations.clone(),
+            }
+        })
+        .collect::<Vec<_>>();
+    let total_length = ext_markers.iter().map(|m| m.length * m.plies as f32).sum::<f32>();
+    info!("[MODE] marker plan requires a total fabric length of {:.3}", total_length);
+
+    Ok(MPOutput {
+        name: ext_instance.name.clone(),
+        strip_height: ext_instance.strip_height,
+        markers: ext_markers,
+        total_length,
+    })
+}
+
+/// Solves a bin packing problem.
+/// Returns the imported instance and the solution along with the output, e.g. to draw the individual sheets.
+pub fn solve_bin_packing(
+    ext_instance: ExtBPInstance,
+    config: &SparrowConfig,
+    rng: ChaCha20Rng,
+    terminator: &mut impl Terminator,
+) -> Result<(BPInstance, BPSolution, BPOutput)> {
+    let importer = Importer::new(
+        config.cde_config,
+        config.poly_simpl_tolerance,
+        config.min_item_separation,
+        config.narrow_concavity_cutoff_ratio,
+    );
+    let instance = jagua_rs::probs::bpp::io::import(&importer, &ext_instance)?;
+
+    info!(
+        "[MODE] loaded bin packing instance {} with #{} items and {} sheet types",
+        ext_instance.name,
+        instance.total_item_qty(),
+        instance.bins.len()
+    );
+
+    let solution = optimize_bpp(
+        instance.clone(),
+        rng,
+        terminator,
+        &config.expl_cfg,
+        &config.cmpr_cfg,
+    )?;
+
+    //items only require the minimum separation, there are no margins along the edges of the sheets
+    let item_features = ItemFeatures {
+        clearances: vec![config.min_item_separation.unwrap_or(0.0); instance.items.len()],
+        ..ItemFeatures::default()
+    };
+    let clearance_violations = solution
+        .layout_snapshots
+        .values()
+        .flat_map(|layout_snapshot| verify_clearances(&instance, layout_snapshot, &item_features, 0.0))
+        .collect();
+
+    let output = BPOutput {
+        solution: jagua_rs::probs::bpp::io::export(&instance, &solution, *EPOCH),
+        instance: ext_instance,
+        clearance_violations,
+    };
+    Ok((instance, solution, output))
+}
+
+/// Solves a knapsack problem: maximizes the total value of the items placed in the container
+pub fn solve_knapsack(
+    ext_instance: ExtKPInstance,
+    ext_item_features: ExtItemFeatures,
+    ext_strip_features: ExtStripFeatures,
+    config: &SparrowConfig,
+    rng: ChaCha20Rng,
+    sol_listener: &mut impl SolutionListener,
+    terminator: &mut impl Terminator,
+) -> Result<KPOutput> {
+    let (instance, item_features, strip_features) =
+        io::import_spp_problem(config, &ext_instance.to_spp(), &ext_item_features, &ext_strip_features)?;
+
+    //items without an explicit value are valued by their area, mirrored twins share the value of their original
+    //and kits are valued by the sum of their parts
+    let item_value = |id: usize| {
+        let ext_item = &ext_instance.items[id];
+        ext_item.value.unwrap_or(instance.item(id).shape_cd.area)
+    };
+    let item_values = (0..instance.items.len())
+        .map(|id| match item_features.kit_parts(id) {
+            [] => item_value(item_features.original(id)),
+            parts => parts.iter().map(|part| item_value(part.item_id)).sum(),
+        })
+        .collect::<Vec<f32>>();
+
+    info!(
+        "[MODE] loaded knapsack instance {} with #{} items in a {}x{} container",
+        ext_instance.name,
+        instance.total_item_qty(),
+        ext_instance.strip_width,
+        ext_instance.strip_height
+    );
+
+    //there is no compression phase, the entire time budget is spent on improving the knapsack
+    let mut expl_config = config.expl_cfg;
+    expl_config.time_limit = config.expl_cfg.time_limit + config.cmpr_cfg.time_limit;
+
+    let solution = optimize_knapsack(
+        instance.clone(),
+        strip_features,
+        item_features.clone(),
+        &item_values,
+        ext_instance.strip_width,
+        rng,
+        sol_listener,
+        terminator,
+        &expl_config,
+    );
+
+    let total_value = solution_value(&solution, &item_values);
+    let unplaced_items = (0..instance.items.len())
+        .filter(|&id| !item_features.is_mirrored(id))
+        .filter_map(|id| {
+            let n_placed = solution
+                .layout_snapshot
+                .placed_items
+                .values()
+                .filter(|pi| item_features.original(pi.item_id) == id)
+                .count();
+            let quantity = instance.items[id].1 - n_placed;
+            (quantity > 0).then_some(ExtUnplacedItem {
+                item_id: id as u64,
+                quantity,
+            })
+        })
+        .collect();
+
+    let exported = ExportedSolution::new(&instance, &solution, &item_features, &ext_strip_features);
+    Ok(KPOutput {
+        solution: exported.solution,
+        instance: ext_instance,
+        strip_features: ext_strip_features,
+        total_value,
+        unplaced_items,
+        mirrored_items: exported.mirrored_items,
+        clearance_violations: exported.clearance_violations,
+    })
+}
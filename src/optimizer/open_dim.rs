@@ -0,0 +1,197 @@
+use crate::config::{CompressionConfig, ExplorationConfig, OpenDimObjective};
+use crate::consts::{LBF_SAMPLE_CONFIG, OPEN_DIM_STEP_DECAY};
+use crate::optimizer::lbf::LBFBuilder;
+use crate::optimizer::problem::{ItemFeatures, StripFeatures};
+use crate::optimizer::separator::Separator;
+use crate::util::listener::{ReportType, SolutionListener};
+use crate::util::terminator::Terminator;
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use log::info;
+use rand::prelude::IndexedRandom;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Axis along which the container can be shrunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    Width,
+    Height,
+}
+
+/// Optimizes an open dimension variant of the strip packing problem: both the width and height of the
+/// container are variable and the goal is to minimize its area or perimeter.
+/// The `strip_height` of the instance is only used for the initial solution.
+pub fn optimize_open_dimension(
+    instance: SPInstance,
+    strip_features: StripFeatures,
+    item_features: ItemFeatures,
+    mut rng: ChaCha20Rng,
+    sol_listener: &mut impl SolutionListener,
+    terminator: &mut impl Terminator,
+    expl_config: &ExplorationConfig,
+    cmpr_config: &CompressionConfig,
+    objective: OpenDimObjective,
+) -> SPSolution {
+    let mut next_rng = || ChaCha20Rng::seed_from_u64(rng.next_u64());
+    let builder = LBFBuilder::new(instance.clone(), next_rng(), LBF_SAMPLE_CONFIG)
+        .with_strip_features(strip_features)
+        .with_item_features(item_features)
+        .construct();
+
+    terminator.new_timeout(expl_config.time_limit + cmpr_config.time_limit);
+    let mut sep = Separator::new(
+        builder.instance,
+        builder.prob,
+        next_rng(),
+        expl_config.separator_config,
+    );
+    let sol = open_dimension_phase(
+        &instance,
+        &mut sep,
+        sol_listener,
+        terminator,
+        expl_config,
+        cmpr_config,
+        objective,
+    );
+
+    sol_listener.report(ReportType::Final, &sol, &instance);
+
+    sol
+}
+
+/// Alternately shrinks the width and height of the container and separates the layout.
+/// The shrink step starts at the exploration step and decays every time neither axis can be shrunk,
+/// until it drops below the smallest step of the compression phase.
+/// Returns the smallest feasible solution found.
+pub fn open_dimension_phase(
+    instance: &SPInstance,
+    sep: &mut Separator,
+    sol_listener: &mut impl SolutionListener,
+    term: &impl Terminator,
+    expl_config: &ExplorationConfig,
+    cmpr_config: &CompressionConfig,
+    objective: OpenDimObjective,
+) -> SPSolution {
+    let init = sep.prob.save();
+    let mut best = init.clone();
+    let mut step = expl_config.shrink_step;
+    let mut failed_axes = vec![];
+
+    sol_listener.report(ReportType::ExplFeas, &best, instance);
+    info!(
+        "[ODIM] starting with container {:.3} x {:.3} ({:.3}%)",
+        best.strip_width(),
+        best.strip.fixed_height,
+        best.density(instance) * 100.0
+    );
+
+    while !term.kill() && step >= cmpr_config.shrink_range.1 {
+        let Some(axis) = select_axis(&best, &failed_axes, objective, &mut sep.rng) else {
+            //neither axis could be shrunk, try again with a smaller step
+            step *= OPEN_DIM_STEP_DECAY;
+            failed_axes.clear();
+            info!("[ODIM] decaying shrink step to {:.3}%", step * 100.0);
+            continue;
+        };
+
+        match attempt_to_shrink(sep, &best, axis, step, term, sol_listener) {
+            Some(sol) => {
+                info!(
+                    "[ODIM] success, shrunk {:?} by {:.3}% ({:.3} x {:.3} | {:.3}%)",
+                    axis,
+                    step * 100.0,
+                    sol.strip_width(),
+                    sol.strip.fixed_height,
+                    sol.density(instance) * 100.0
+                );
+                sol_listener.report(ReportType::ExplFeas, &sol, instance);
+                best = sol;
+                failed_axes.clear();
+            }
+            None => {
+                info!("[ODIM] failed to shrink {:?} by {:.3}%", axis, step * 100.0);
+                failed_axes.push(axis);
+            }
+        }
+    }
+
+    //leave the separator in the state of the best solution
+    sep.change_strip_width(best.strip_width(), None);
+    sep.change_strip_height(best.strip.fixed_height, None);
+    sep.rollback(&best, None);
+
+    info!(
+        "[ODIM] finished, shrunk container from {:.3} x {:.3} to {:.3} x {:.3} ({:.3}%)",
+        init.strip_width(),
+        init.strip.fixed_height,
+        best.strip_width(),
+        best.strip.fixed_height,
+        best.density(instance) * 100.0
+    );
+
+    best
+}
+
+/// Selects the axis to shrink next, among the ones which did not fail yet at the current step.
+/// For the area objective both axes are equally attractive, for the perimeter the longest side is shrunk first.
+fn select_axis(
+    sol: &SPSolution,
+    failed_axes: &[Axis],
+    objective: OpenDimObjective,
+    rng: &mut impl Rng,
+) -> Option<Axis> {
+    let candidates = [Axis::Width, Axis::Height]
+        .into_iter()
+        .filter(|a| !failed_axes.contains(a))
+        .collect::<Vec<_>>();
+
+    match objective {
+        OpenDimObjective::Area => candidates.choose(rng).copied(),
+        OpenDimObjective::Perimeter => {
+            let longest = match sol.strip_width() >= sol.strip.fixed_height {
+                true => Axis::Width,
+                false => Axis::Height,
+            };
+            match candidates.contains(&longest) {
+                true => Some(longest),
+                false => candidates.first().copied(),
+            }
+        }
+    }
+}
+
+fn attempt_to_shrink(
+    sep: &mut Separator,
+    init: &SPSolution,
+    axis: Axis,
+    r_shrink: f32,
+    term: &impl Terminator,
+    sol_listener: &mut impl SolutionListener,
+) -> Option<SPSolution> {
+    //restore to the initial solution and container
+    sep.change_strip_width(init.strip_width(), None);
+    sep.change_strip_height(init.strip.fixed_height, None);
+    sep.rollback(init, None);
+
+    //shrink the container along the axis at a random position
+    match axis {
+        Axis::Width => {
+            let new_width = init.strip_width() * (1.0 - r_shrink);
+            let split_pos = sep.rng.random_range(0.0..sep.prob.strip_width());
+            sep.change_strip_width(new_width, Some(split_pos));
+        }
+        Axis::Height => {
+            let new_height = init.strip.fixed_height * (1.0 - r_shrink);
+            let split_pos = sep.rng.random_range(0.0..sep.prob.strip_height());
+            sep.change_strip_height(new_height, Some(split_pos));
+        }
+    }
+
+    //try to separate layout, if all collisions are eliminated, return the solution
+    let (shrunk_sol, ct) = sep.separate(term, sol_listener);
+    match ct.get_total_loss() == 0.0 {
+        true => Some(shrunk_sol),
+        false => None,
+    }
+}
@@ -0,0 +1,383 @@
+use jagua_rs::entities::{Container, InferiorQualityZone, Instance, Layout, PItemKey};
+use jagua_rs::geometry::DTransformation;
+use jagua_rs::geometry::original_shape::OriginalShape;
+use jagua_rs::geometry::primitives::{Rect, SPolygon};
+use jagua_rs::geometry::shape_modification::{ShapeModifyConfig, ShapeModifyMode};
+use jagua_rs::probs::spp::entities::{SPInstance, SPPlacement, SPProblem, SPSolution};
+use crate::consts::MIN_STRIP_WIDTH_MARGIN_RATIO;
+use crate::quantify::proximity::ProximityGroups;
+use crate::sample::rot_intervals::RotIntervals;
+use itertools::Itertools;
+use ordered_float::OrderedFloat;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// Abstraction over the problem types on which the [`Separator`](crate::optimizer::separator::Separator) can operate.
+/// The separator only moves items around within a single [`Layout`], everything else is up to the problem.
+pub trait SeparableProblem: Clone + Send + Sync {
+    type Instance: Instance + Clone + Send + Sync;
+    type Solution: Clone + Send + Sync;
+
+    /// The layout in which the items are separated
+    fn layout(&self) -> &Layout;
+
+    /// Places an item in the layout, regardless of whether it collides with anything
+    fn place_item(&mut self, item_id: usize, d_transf: DTransformation) -> PItemKey;
+
+    /// Removes a placed item from the layout
+    fn remove_item(&mut self, pk: PItemKey);
+
+    fn save(&mut self) -> Self::Solution;
+
+    fn restore(&mut self, sol: &Self::Solution);
+
+    /// The mirrored twin of an item, if the item is allowed to be mirrored
+    fn mirror_twin(&self, _item_id: usize) -> Option<usize> {
+        None
+    }
+
+    /// Holes of every item in which other items can be nested, empty if no item has holes
+    fn item_holes(&self) -> Arc<[Vec<SPolygon>]> {
+        Arc::default()
+    }
+
+    /// Regions of the container to which the items are restricted, empty if no item has a region
+    fn item_regions(&self) -> Arc<[Option<Rect>]> {
+        Arc::default()
+    }
+
+    /// Groups of items which should be placed close to each other (soft constraint)
+    fn proximity_groups(&self) -> Arc<ProximityGroups> {
+        Arc::default()
+    }
+
+    /// Intervals to which the rotation of an item is restricted, if any
+    fn rot_intervals(&self, _item_id: usize) -> Option<&RotIntervals> {
+        None
+    }
+
+    /// Costs (in radians, cost) of the disfavored orientations of an item, empty if it has none
+    fn orientation_costs(&self, _item_id: usize) -> &[(f32, f32)] {
+        &[]
+    }
+
+    /// Whether the placed item is pinned to its position, pinned items are never moved
+    fn is_pinned(&self, _pk: PItemKey) -> bool {
+        false
+    }
+}
+
+/// Properties of the strip which are not supported by the plain rectangular strip of jagua-rs
+#[derive(Clone, Debug, Default)]
+pub struct StripFeatures {
+    /// Defects (holes) at fixed positions in the strip, no item is allowed to overlap with them
+    pub defects: Vec<OriginalShape>,
+    /// Zones of inferior quality, items requiring a higher quality are not allowed to overlap with them
+    pub quality_zones: Vec<InferiorQualityZone>,
+    /// Margin along all edges of the strip which cannot be used, on top of the clearance of the items
+    pub border_margin: f32,
+    /// Items at fixed positions in the strip (e.g. on a remnant), the other items are nested around them
+    pub pinned_items: Vec<SPPlacement>,
+}
+
+impl StripFeatures {
+    /// Whether the container is the plain rectangular strip of jagua-rs
+    pub fn is_empty(&self) -> bool {
+        self.defects.is_empty() && self.quality_zones.is_empty() && self.border_margin == 0.0
+    }
+
+    /// Bands along the edges of a strip with the given dimensions, covering the border margin
+    fn border_bands(&self, width: f32, height: f32) -> Vec<OriginalShape> {
+        let m = self.border_margin;
+        if m == 0.0 {
+            return vec![];
+        }
+        [
+            Rect::try_new(0.0, 0.0, m, height),
+            Rect::try_new(width - m, 0.0, width, height),
+            Rect::try_new(0.0, 0.0, width, m),
+            Rect::try_new(0.0, height - m, width, height),
+        ]
+        .into_iter()
+        .map(|r| OriginalShape {
+            shape: SPolygon::from(r.expect("border margin should fit within the strip")),
+            pre_transform: DTransformation::empty(),
+            //like the defects, the bands are inflated by the clearance of the items
+            modify_mode: ShapeModifyMode::Inflate,
+            modify_config: ShapeModifyConfig::default(),
+        })
+        .collect()
+    }
+
+    /// Drops the copies of the pinned items from the placements of a solution.
+    /// For every pinned item, the placement of the same item closest to it is dropped,
+    /// as pinned items are not necessarily at the exact same position after exporting.
+    /// The order of the other placements is preserved.
+    pub fn without_pinned(&self, mut placements: Vec<SPPlacement>) -> Vec<SPPlacement> {
+        for pinned in self.pinned_items.iter() {
+            let (px, py) = pinned.d_transf.translation();
+            let closest = placements
+                .iter()
+                .positions(|p| p.item_id == pinned.item_id)
+                .min_by_key(|&i| {
+                    let (x, y) = placements[i].d_transf.translation();
+                    OrderedFloat((x - px).powi(2) + (y - py).powi(2))
+                });
+            if let Some(i) = closest {
+                placements.remove(i);
+            }
+        }
+        placements
+    }
+}
+
+/// Properties of the items which are not supported by jagua-rs.
+/// jagua-rs cannot mirror items, so every item which is allowed to be mirrored has a mirrored twin in the instance.
+/// Twins are appended after all original items and share the demand of the original.
+#[derive(Clone, Debug, Default)]
+pub struct ItemFeatures {
+    /// For every item, its mirrored twin (if any). Twins refer to each other.
+    pub mirror_twins: Vec<Option<usize>>,
+    /// For every item, the holes in its shape in which other items can be nested (parts-in-parts).
+    /// Defined in the reference frame of the item. Empty if no item has holes.
+    pub holes: Arc<[Vec<SPolygon>]>,
+    /// For every item, the minimum clearance (e.g. kerf) it requires to other items.
+    /// Every item is inflated by half of its clearance, so the clearance between two items is the mean of both.
+    pub clearances: Vec<f32>,
+    /// For every item, the intervals to which its rotation is restricted (if any)
+    pub rot_intervals: Vec<Option<RotIntervals>>,
+    /// For every item, the parts it consists of if it is a kit. Empty for regular items.
+    pub kits: Vec<Vec<KitPart>>,
+    /// For every (original) item, the number of optional copies on top of its demand, used to fill up the strip.
+    /// Empty if no item has optional copies.
+    pub extra_qtys: Vec<usize>,
+    /// For every item, the region of the strip (e.g. near the selvedge) it has to lie within (if any).
+    /// Leaving the region is treated as a collision with the container. Empty if no item has a region.
+    pub regions: Arc<[Option<Rect>]>,
+    /// Groups of items which should be placed close to each other along the strip (soft constraint)
+    pub proximity: Arc<ProximityGroups>,
+    /// For every item, the costs of its disfavored orientations as (rotation in radians, cost) pairs.
+    /// A soft constraint: disfavored orientations are allowed, but avoided when it costs little density.
+    /// Empty if no item has orientation costs.
+    pub orientation_costs: Vec<Vec<(f32, f32)>>,
+}
+
+/// Part of a kit: a group of items which is placed as a single rigid unit.
+/// Kits are regular items for the optimizer, their parts are only placed individually when exporting a solution.
+#[derive(Clone, Debug)]
+pub struct KitPart {
+    /// The (original) item the part consists of
+    pub item_id: usize,
+    /// Position of the part relative to the kit
+    pub offset: DTransformation,
+}
+
+impl ItemFeatures {
+    pub fn mirror_twin(&self, item_id: usize) -> Option<usize> {
+        self.mirror_twins.get(item_id).copied().flatten()
+    }
+
+    pub fn rot_intervals(&self, item_id: usize) -> Option<&RotIntervals> {
+        self.rot_intervals.get(item_id).and_then(|ri| ri.as_ref())
+    }
+
+    pub fn orientation_costs(&self, item_id: usize) -> &[(f32, f32)] {
+        self.orientation_costs.get(item_id).map_or(&[], |c| c.as_slice())
+    }
+
+    pub fn region(&self, item_id: usize) -> Option<Rect> {
+        self.regions.get(item_id).copied().flatten()
+    }
+
+    /// The parts of the item if it is a kit, empty otherwise
+    pub fn kit_parts(&self, item_id: usize) -> &[KitPart] {
+        self.kits.get(item_id).map_or(&[], |parts| parts.as_slice())
+    }
+
+    /// Whether the item is the mirrored twin of an original item
+    pub fn is_mirrored(&self, item_id: usize) -> bool {
+        self.mirror_twin(item_id).is_some_and(|twin| twin < item_id)
+    }
+
+    /// The original (non-mirrored) variant of the item
+    pub fn original(&self, item_id: usize) -> usize {
+        match self.is_mirrored(item_id) {
+            true => self.mirror_twin(item_id).unwrap(),
+            false => item_id,
+        }
+    }
+}
+
+/// Strip packing problem extended with the [`StripFeatures`] of the strip and the [`ItemFeatures`] of the items.
+/// Every time jagua-rs replaces the container of the strip (e.g. when changing its width),
+/// the features are applied to the new container.
+#[derive(Clone)]
+pub struct StripProblem {
+    pub prob: SPProblem,
+    pub features: StripFeatures,
+    pub item_features: ItemFeatures,
+    /// Keys of the placed pinned items, these keep their key as long as they are never removed
+    pub pinned_pks: Vec<PItemKey>,
+}
+
+impl StripProblem {
+    pub fn new(instance: SPInstance, features: StripFeatures) -> Self {
+        let mut sp = Self {
+            prob: SPProblem::new(instance),
+            features,
+            item_features: ItemFeatures::default(),
+            pinned_pks: vec![],
+        };
+        sp.apply_features();
+        sp
+    }
+
+    /// Changes the width of the strip, which never becomes narrower than the border margin on both sides
+    pub fn change_strip_width(&mut self, new_width: f32) {
+        let new_width = f32::max(new_width, (2.0 + MIN_STRIP_WIDTH_MARGIN_RATIO) * self.features.border_margin);
+        self.prob.change_strip_width(new_width);
+        self.apply_features();
+    }
+
+    /// Changes the height of the strip, which is normally fixed.
+    /// Like the width, it never becomes narrower than the border margin on both sides.
+    pub fn change_strip_height(&mut self, new_height: f32) {
+        let new_height = f32::max(new_height, (2.0 + MIN_STRIP_WIDTH_MARGIN_RATIO) * self.features.border_margin);
+        self.prob.strip.fixed_height = new_height;
+        self.prob.layout.swap_container(Container::from(self.prob.strip));
+        self.apply_features();
+    }
+
+    pub fn strip_height(&self) -> f32 {
+        self.prob.strip.fixed_height
+    }
+
+    /// Number of copies of an (original) item which are not placed yet, in either of its variants
+    pub fn missing_qty(&self, item_id: usize) -> usize {
+        let demand = &self.prob.item_demand_qtys;
+        match self.item_features.mirror_twin(item_id) {
+            //both variants start out with the full demand of the item
+            Some(twin) => demand[item_id] + demand[twin] - self.prob.instance.items[item_id].1,
+            None => demand[item_id],
+        }
+    }
+
+    /// Places all pinned items in the layout, their demand is taken from the demand of the items
+    pub fn place_pinned_items(&mut self) {
+        debug_assert!(self.prob.layout.placed_items.is_empty());
+        self.pinned_pks = self
+            .features
+            .pinned_items
+            .clone()
+            .into_iter()
+            .map(|p| self.prob.place_item(p))
+            .collect();
+    }
+
+    pub fn fit_strip(&mut self) {
+        self.prob.fit_strip();
+        if self.features.border_margin > 0.0 {
+            //leave room for the margin at the right edge of the strip
+            let width = self.prob.strip_width() + self.features.border_margin;
+            self.prob.change_strip_width(width);
+        }
+        self.apply_features();
+    }
+
+    fn apply_features(&mut self) {
+        if self.features.is_empty() {
+            return;
+        }
+        let strip_width = self.prob.strip_width();
+        let container = &self.prob.layout.container;
+
+        //only defects which (partially) lie within the strip are relevant
+        let holes = self
+            .features
+            .defects
+            .iter()
+            .filter(|d| d.shape.bbox.x_min < strip_width)
+            .cloned()
+            .chain(self.features.border_bands(strip_width, self.prob.strip.fixed_height))
+            .collect();
+
+        let container = Container::new(
+            container.id,
+            container.outer_orig.as_ref().clone(),
+            holes,
+            self.features.quality_zones.clone(),
+            self.prob.strip.cde_config,
+        )
+        .expect("strip features should be valid for the container");
+        self.prob.layout.swap_container(container);
+    }
+}
+
+impl Deref for StripProblem {
+    type Target = SPProblem;
+
+    fn deref(&self) -> &SPProblem {
+        &self.prob
+    }
+}
+
+impl DerefMut for StripProblem {
+    fn deref_mut(&mut self) -> &mut SPProblem {
+        &mut self.prob
+    }
+}
+
+impl SeparableProblem for StripProblem {
+    type Instance = SPInstance;
+    type Solution = SPSolution;
+
+    fn layout(&self) -> &Layout {
+        &self.prob.layout
+    }
+
+    fn place_item(&mut self, item_id: usize, d_transf: DTransformation) -> PItemKey {
+        self.prob.place_item(SPPlacement { item_id, d_transf })
+    }
+
+    fn remove_item(&mut self, pk: PItemKey) {
+        self.prob.remove_item(pk);
+    }
+
+    fn save(&mut self) -> SPSolution {
+        self.prob.save()
+    }
+
+    fn restore(&mut self, sol: &SPSolution) {
+        debug_assert!(sol.strip_width() == self.prob.strip_width());
+        //the snapshot contains the container of the solution, defects included
+        self.prob.restore(sol);
+    }
+
+    fn mirror_twin(&self, item_id: usize) -> Option<usize> {
+        self.item_features.mirror_twin(item_id)
+    }
+
+    fn item_holes(&self) -> Arc<[Vec<SPolygon>]> {
+        self.item_features.holes.clone()
+    }
+
+    fn item_regions(&self) -> Arc<[Option<Rect>]> {
+        self.item_features.regions.clone()
+    }
+
+    fn proximity_groups(&self) -> Arc<ProximityGroups> {
+        self.item_features.proximity.clone()
+    }
+
+    fn rot_intervals(&self, item_id: usize) -> Option<&RotIntervals> {
+        self.item_features.rot_intervals(item_id)
+    }
+
+    fn orientation_costs(&self, item_id: usize) -> &[(f32, f32)] {
+        self.item_features.orientation_costs(item_id)
+    }
+
+    fn is_pinned(&self, pk: PItemKey) -> bool {
+        self.pinned_pks.contains(&pk)
+    }
+}
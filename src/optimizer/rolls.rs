@@ -0,0 +1,181 @@
+use crate::config::{CompressionConfig, ExplorationConfig};
+use crate::consts::{LBF_SAMPLE_CONFIG, ROLL_SELECTION_ROUNDS};
+use crate::optimizer::compress::compression_phase;
+use crate::optimizer::explore::exploration_phase;
+use crate::optimizer::lbf::LBFBuilder;
+use crate::optimizer::problem::{ItemFeatures, StripFeatures};
+use crate::optimizer::separator::Separator;
+use crate::sample::uniform_sampler::UniformBBoxSampler;
+use crate::util::listener::{DummySolListener, ReportType, SolutionListener};
+use crate::util::terminator::Terminator;
+use itertools::Itertools;
+use jagua_rs::entities::Instance;
+use jagua_rs::geometry::primitives::Rect;
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use log::info;
+use ordered_float::OrderedFloat;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// A roll which can be used as strip: its width (the height of the strip) and cost per unit of length
+#[derive(Debug, Clone, Copy)]
+pub struct Roll {
+    pub height: f32,
+    pub cost: f32,
+}
+
+/// Outcome of the optimization for a single roll of the catalogue
+#[derive(Debug, Clone)]
+pub struct RollSummary {
+    pub roll: Roll,
+    /// Width of the best feasible solution found on this roll, if any
+    pub strip_width: Option<f32>,
+    /// Round in which the roll was dropped, `None` if it survived until the end
+    pub dropped_in_round: Option<usize>,
+}
+
+impl RollSummary {
+    pub fn total_cost(&self) -> Option<f32> {
+        self.strip_width.map(|w| w * self.roll.cost)
+    }
+}
+
+/// Selects the cheapest roll from a catalogue to cut all items from.
+/// The exploration time budget is shared over the rolls in a number of rounds. After every round, rolls which
+/// can no longer beat the cheapest one (even at full density) and the most expensive half are dropped,
+/// so that the more promising rolls receive more time in later rounds.
+/// The cheapest roll is then compressed further.
+/// Returns the index of the selected roll, its solution and a summary of all rolls.
+pub fn optimize_roll_selection(
+    instance: SPInstance,
+    strip_features: StripFeatures,
+    item_features: ItemFeatures,
+    rolls: &[Roll],
+    mut rng: ChaCha20Rng,
+    sol_listener: &mut impl SolutionListener,
+    terminator: &mut impl Terminator,
+    expl_config: &ExplorationConfig,
+    cmpr_config: &CompressionConfig,
+) -> Option<(usize, SPSolution, Vec<RollSummary>)> {
+    let mut next_rng = || ChaCha20Rng::seed_from_u64(rng.next_u64());
+    let mut summaries = rolls
+        .iter()
+        .map(|roll| RollSummary {
+            roll: *roll,
+            strip_width: None,
+            dropped_in_round: None,
+        })
+        .collect_vec();
+
+    let mut candidates = vec![];
+    for (i, roll) in rolls.iter().enumerate() {
+        //rolls which are not high enough for some of the items are hopeless from the start
+        if !all_items_fit(&instance, &item_features, roll.height - 2.0 * strip_features.border_margin) {
+            info!("[ROLL] items do not fit on roll {} (height: {:.3}), dropping it", i, roll.height);
+            summaries[i].dropped_in_round = Some(0);
+            continue;
+        }
+        let mut builder = LBFBuilder::new(instance.clone(), next_rng(), LBF_SAMPLE_CONFIG)
+            .with_strip_features(strip_features.clone())
+            .with_item_features(item_features.clone());
+        builder.prob.change_strip_height(roll.height);
+        let builder = builder.construct();
+        let mut sep = Separator::new(
+            builder.instance,
+            builder.prob,
+            next_rng(),
+            expl_config.separator_config,
+        );
+        let sol = sep.prob.save();
+        summaries[i].strip_width = Some(sol.strip_width());
+        candidates.push((i, sep, sol));
+    }
+
+    let total_item_area = instance
+        .items
+        .iter()
+        .filter(|(item, _)| !item_features.is_mirrored(item.id))
+        .map(|(item, qty)| item.shape_cd.area * *qty as f32)
+        .sum::<f32>();
+
+    for round in 1..=ROLL_SELECTION_ROUNDS {
+        if candidates.is_empty() || terminator.kill() {
+            break;
+        }
+        let slice = expl_config.time_limit / (ROLL_SELECTION_ROUNDS * candidates.len()) as u32;
+        info!(
+            "[ROLL] round {}/{}: exploring {} rolls for {:.1}s each",
+            round,
+            ROLL_SELECTION_ROUNDS,
+            candidates.len(),
+            slice.as_secs_f32()
+        );
+
+        for (i, sep, best) in candidates.iter_mut() {
+            //continue from the best feasible solution of the previous round
+            sep.change_strip_width(best.strip_width(), None);
+            sep.rollback(best, None);
+
+            terminator.new_timeout(slice);
+            let solutions = exploration_phase(&instance, sep, &mut DummySolListener, terminator, expl_config);
+            let sol = solutions.last().unwrap();
+            if sol.strip_width() < best.strip_width() {
+                *best = sol.clone();
+            }
+            summaries[*i].strip_width = Some(best.strip_width());
+            info!(
+                "[ROLL] roll {} (height: {:.3}): width {:.3}, cost {:.3}",
+                i,
+                rolls[*i].height,
+                best.strip_width(),
+                summaries[*i].total_cost().unwrap()
+            );
+        }
+
+        //drop all rolls which cannot become cheaper than the current cheapest one, even at full density
+        let cheapest_cost = candidates
+            .iter()
+            .map(|(i, _, _)| summaries[*i].total_cost().unwrap())
+            .fold(f32::INFINITY, f32::min);
+        let lower_bound_cost = |i: usize| total_item_area / rolls[i].height * rolls[i].cost;
+
+        candidates.sort_by_key(|(i, _, _)| OrderedFloat(summaries[*i].total_cost().unwrap()));
+        let n_keep = match round < ROLL_SELECTION_ROUNDS {
+            true => candidates.len().div_ceil(2),
+            false => 1,
+        };
+        for (idx, (i, _, _)) in candidates.iter().enumerate() {
+            if idx >= n_keep || (idx > 0 && lower_bound_cost(*i) >= cheapest_cost) {
+                info!("[ROLL] dropping roll {} in round {}", i, round);
+                summaries[*i].dropped_in_round = Some(round);
+            }
+        }
+        candidates.retain(|(i, _, _)| summaries[*i].dropped_in_round.is_none());
+    }
+
+    //compress the layout on the cheapest roll
+    let (selected, sep, best) = candidates.into_iter().next()?;
+    info!(
+        "[ROLL] selected roll {} (height: {:.3}, cost: {:.3})",
+        selected,
+        rolls[selected].height,
+        summaries[selected].total_cost().unwrap()
+    );
+    terminator.new_timeout(cmpr_config.time_limit);
+    sol_listener.report(ReportType::ExplFeas, &best, &instance);
+    let mut cmpr_sep = Separator::new(sep.instance, sep.prob, next_rng(), cmpr_config.separator_config);
+    let cmpr_sol = compression_phase(&instance, &mut cmpr_sep, &best, sol_listener, terminator, cmpr_config);
+    summaries[selected].strip_width = Some(cmpr_sol.strip_width());
+
+    sol_listener.report(ReportType::Final, &cmpr_sol, &instance);
+
+    Some((selected, cmpr_sol, summaries))
+}
+
+/// Checks whether every item fits (in at least one of its allowed orientations) in a strip of the given height
+fn all_items_fit(instance: &SPInstance, item_features: &ItemFeatures, height: f32) -> bool {
+    height > 0.0 && instance.items.iter().all(|(item, _)| {
+        let bbox = Rect::try_new(0.0, 0.0, 2.0 * item.shape_cd.diameter, height).unwrap();
+        UniformBBoxSampler::new(bbox, item, bbox, item_features.rot_intervals(item.id)).is_some()
+    })
+}
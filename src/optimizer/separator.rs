@@ -1,5 +1,6 @@
 use crate::FMT;
 use crate::Instant;
+use crate::optimizer::problem::{SeparableProblem, StripProblem};
 use crate::optimizer::worker::{SepStats, SeparatorWorker};
 use crate::quantify::tracker::{CTSnapshot, CollisionTracker};
 use crate::sample::search::SampleConfig;
@@ -9,7 +10,6 @@ use crate::util::terminator::Terminator;
 use itertools::Itertools;
 use jagua_rs::entities::PItemKey;
 use jagua_rs::geometry::DTransformation;
-use jagua_rs::probs::spp::entities::{SPInstance, SPPlacement, SPProblem, SPSolution};
 use log::{Level, debug, log};
 use ordered_float::OrderedFloat;
 use rand::rngs::SmallRng;
@@ -30,25 +30,29 @@ pub struct SeparatorConfig {
     pub sample_config: SampleConfig,
 }
 
-pub struct Separator {
-    pub instance: SPInstance,
+pub struct Separator<P: SeparableProblem = StripProblem> {
+    pub instance: P::Instance,
     pub rng: ChaCha20Rng,
-    pub prob: SPProblem,
+    pub prob: P,
     pub ct: CollisionTracker,
-    pub workers: Vec<SeparatorWorker>,
+    pub workers: Vec<SeparatorWorker<P>>,
     pub config: SeparatorConfig,
     #[cfg(not(target_arch = "wasm32"))]
     pub pool: ThreadPool,
 }
 
-impl Separator {
+impl<P: SeparableProblem> Separator<P> {
     pub fn new(
-        instance: SPInstance,
-        prob: SPProblem,
+        instance: P::Instance,
+        prob: P,
         mut rng: ChaCha20Rng,
         config: SeparatorConfig,
     ) -> Self {
-        let ct = CollisionTracker::new(&prob.layout);
+        let ct = CollisionTracker::new(
+            prob.layout(),
+            prob.item_holes(),
+            prob.item_regions(),
+        );
         let workers = (0..config.n_workers)
             .map(|_| SeparatorWorker {
                 instance: instance.clone(),
@@ -80,14 +84,13 @@ impl Separator {
     pub fn separate(
         &mut self,
         term: &impl Terminator,
-        sol_listener: &mut impl SolutionListener,
-    ) -> (SPSolution, CTSnapshot) {
+        sol_listener: &mut impl SolutionListener<P>,
+    ) -> (P::Solution, CTSnapshot) {
         let mut min_loss_sol = (self.prob.save(), self.ct.save());
         let mut min_loss = self.ct.get_total_loss();
         log!(
             self.config.log_level,
-            "[SEP] separating at width: {:.3} and loss: {} ",
-            self.prob.strip_width(),
+            "[SEP] separating at loss: {} ",
             FMT().fmt2(min_loss)
         );
 
@@ -238,26 +241,48 @@ impl Separator {
         sep_report
     }
 
-    pub fn rollback(&mut self, sol: &SPSolution, ots: Option<&CTSnapshot>) {
-        debug_assert!(sol.strip_width() == self.prob.strip_width());
+    pub fn rollback(&mut self, sol: &P::Solution, ots: Option<&CTSnapshot>) {
         self.prob.restore(sol);
 
         match ots {
             Some(ots) => {
                 //if a snapshot of the tracker was provided, restore it
-                self.ct.restore_but_keep_weights(ots, &self.prob.layout);
+                self.ct.restore_but_keep_weights(ots, self.prob.layout());
             }
             None => {
                 //otherwise, rebuild it
-                self.ct = CollisionTracker::new(&self.prob.layout);
+                self.ct = CollisionTracker::new(
+                    self.prob.layout(),
+                    self.prob.item_holes(),
+                    self.prob.item_regions(),
+                );
             }
         }
     }
 
+    /// Replaces the problem of the separator and its workers by another one (e.g. another sheet),
+    /// reusing the workers and the thread pool instead of creating a new separator.
+    pub fn reload(&mut self, prob: P) {
+        self.ct = CollisionTracker::new(
+            prob.layout(),
+            prob.item_holes(),
+            prob.item_regions(),
+        );
+        for worker in self.workers.iter_mut() {
+            worker.prob = prob.clone();
+            worker.ct = self.ct.clone();
+        }
+        self.prob = prob;
+    }
+
     pub fn move_item(&mut self, pk: PItemKey, d_transf: DTransformation) -> PItemKey {
-        debug_assert!(tracker_matches_layout(&self.ct, &self.prob.layout));
+        let item_id = self.prob.layout().placed_items[pk].item_id;
+        self.move_item_as(pk, item_id, d_transf)
+    }
 
-        let item_id = self.prob.layout.placed_items[pk].item_id;
+    /// Moves the item to a new position as `item_id`, which is either the item itself or its mirrored twin.
+    pub fn move_item_as(&mut self, pk: PItemKey, item_id: usize, d_transf: DTransformation) -> PItemKey {
+        debug_assert!(tracker_matches_layout(&self.ct, self.prob.layout()));
 
         let old_loss = self.ct.get_loss(pk);
         let old_weighted_loss = self.ct.get_weighted_loss(pk);
@@ -266,9 +291,9 @@ impl Separator {
         self.prob.remove_item(pk);
 
         //Place the item again but with a new transformation
-        let new_pk = self.prob.place_item(SPPlacement { d_transf, item_id });
+        let new_pk = self.prob.place_item(item_id, d_transf);
 
-        self.ct.register_item_move(&self.prob.layout, pk, new_pk);
+        self.ct.register_item_move(self.prob.layout(), pk, new_pk);
 
         let new_loss = self.ct.get_loss(new_pk);
         let new_weighted_loss = self.ct.get_weighted_loss(new_pk);
@@ -282,23 +307,42 @@ impl Separator {
             FMT().fmt2(new_weighted_loss)
         );
 
-        debug_assert!(tracker_matches_layout(&self.ct, &self.prob.layout));
+        debug_assert!(tracker_matches_layout(&self.ct, self.prob.layout()));
 
         new_pk
     }
 
+    /// Places an additional item in the layout, collisions are allowed.
+    pub fn place_item(&mut self, item_id: usize, d_transf: DTransformation) -> PItemKey {
+        let pk = self.prob.place_item(item_id, d_transf);
+        self.ct.register_item_insert(self.prob.layout(), pk);
+        debug!("[SEP] placed additional item {}", item_id);
+
+        pk
+    }
+
+    /// Removes an item from the layout.
+    pub fn remove_item(&mut self, pk: PItemKey) {
+        let item_id = self.prob.layout().placed_items[pk].item_id;
+        self.prob.remove_item(pk);
+        self.ct.register_item_removal(self.prob.layout(), pk);
+        debug!("[SEP] removed item {}", item_id);
+    }
+}
+
+impl Separator<StripProblem> {
     pub fn change_strip_width(&mut self, new_width: f32, split_position: Option<f32>) {
         //if no split position is provided, use the center of the strip
         let split_position = split_position.unwrap_or(self.prob.strip_width() / 2.0);
         let delta = new_width - self.prob.strip_width();
 
-        //shift all items right of the split position
+        //shift all items right of the split position, except the pinned ones
         let items_to_shift = self
             .prob
             .layout
             .placed_items
             .iter()
-            .filter(|(_, pi)| pi.shape.centroid().0 > split_position)
+            .filter(|(pk, pi)| pi.shape.centroid().0 > split_position && !self.prob.is_pinned(*pk))
             .map(|(k, pi)| (k, pi.d_transf))
             .collect_vec();
 
@@ -310,8 +354,45 @@ impl Separator {
 
         self.prob.change_strip_width(new_width);
 
+        self.rebuild_after_container_change();
+        debug!("[SEP] changed strip width to {:.3}", new_width);
+    }
+
+    /// Analogous to [`Self::change_strip_width`], but for the (normally fixed) height of the strip.
+    pub fn change_strip_height(&mut self, new_height: f32, split_position: Option<f32>) {
+        //if no split position is provided, use the center of the strip
+        let split_position = split_position.unwrap_or(self.prob.strip_height() / 2.0);
+        let delta = new_height - self.prob.strip_height();
+
+        //shift all items above the split position, except the pinned ones
+        let items_to_shift = self
+            .prob
+            .layout
+            .placed_items
+            .iter()
+            .filter(|(pk, pi)| pi.shape.centroid().1 > split_position && !self.prob.is_pinned(*pk))
+            .map(|(k, pi)| (k, pi.d_transf))
+            .collect_vec();
+
+        for (pik, dtransf) in items_to_shift {
+            let existing_transf = dtransf.compose();
+            let new_transf = existing_transf.translate((0.0, delta));
+            self.move_item(pik, new_transf.decompose());
+        }
+
+        self.prob.change_strip_height(new_height);
+
+        self.rebuild_after_container_change();
+        debug!("[SEP] changed strip height to {:.3}", new_height);
+    }
+
+    fn rebuild_after_container_change(&mut self) {
         //rebuild the collision tracker
-        self.ct = CollisionTracker::new(&self.prob.layout);
+        self.ct = CollisionTracker::new(
+            &self.prob.layout,
+            self.prob.item_holes(),
+            self.prob.item_regions(),
+        );
 
         //rebuild the workers
         self.workers.iter_mut().for_each(|opt| {
@@ -323,6 +404,5 @@ impl Separator {
                 sample_config: self.config.sample_config.clone(),
             };
         });
-        debug!("[SEP] changed strip width to {:.3}", new_width);
     }
 }
@@ -1,5 +1,7 @@
 use crate::FMT;
+use crate::eval::sample_eval::SampleEval;
 use crate::eval::sep_evaluator::SeparationEvaluator;
+use crate::optimizer::problem::SeparableProblem;
 use crate::quantify::tracker::CollisionTracker;
 use crate::sample::search;
 use crate::sample::search::SampleConfig;
@@ -7,26 +9,25 @@ use crate::util::assertions::tracker_matches_layout;
 use itertools::Itertools;
 use jagua_rs::entities::{Instance, PItemKey};
 use jagua_rs::geometry::DTransformation;
-use jagua_rs::probs::spp::entities::{SPInstance, SPPlacement, SPProblem, SPSolution};
 use log::debug;
 use rand::prelude::{SliceRandom, SmallRng};
 use rand_chacha::ChaCha20Rng;
+use std::iter;
 use std::iter::Sum;
 use std::ops::AddAssign;
 use tap::Tap;
 
-pub struct SeparatorWorker {
-    pub instance: SPInstance,
-    pub prob: SPProblem,
+pub struct SeparatorWorker<P: SeparableProblem> {
+    pub instance: P::Instance,
+    pub prob: P,
     pub ct: CollisionTracker,
     pub rng: ChaCha20Rng,
     pub sample_config: SampleConfig,
 }
 
-impl SeparatorWorker {
-    pub fn load(&mut self, sol: &SPSolution, ct: &CollisionTracker) {
+impl<P: SeparableProblem> SeparatorWorker<P> {
+    pub fn load(&mut self, sol: &P::Solution, ct: &CollisionTracker) {
         // restores the state of the worker to the given solution and accompanying tracker
-        debug_assert!(sol.strip_width() == self.prob.strip_width());
         self.prob.restore(sol);
         self.ct = ct.clone();
     }
@@ -35,43 +36,68 @@ impl SeparatorWorker {
         //collect all colliding items and shuffle them
         let candidates = self
             .prob
-            .layout
+            .layout()
             .placed_items
             .keys()
-            .filter(|pk| self.ct.get_loss(*pk) > 0.0)
+            .filter(|pk| self.ct.get_loss(*pk) > 0.0 && !self.prob.is_pinned(*pk))
             .collect_vec()
             .tap_mut(|v| v.shuffle(&mut self.rng));
 
         let mut total_moves = 0;
         let mut total_evals = 0;
+        let proximity = self.prob.proximity_groups();
 
         //give each item a chance to move to a better (eval) position
         for &pk in candidates.iter() {
             //check if the item is still colliding
             if self.ct.get_loss(pk) > 0.0 {
-                let item_id = self.prob.layout.placed_items[pk].item_id;
-                let item = self.instance.item(item_id);
-
-                // create an evaluator to evaluate the samples during the search
-                let evaluator = SeparationEvaluator::new(&self.prob.layout, item, pk, &self.ct);
-
-                // search for a better position for the item
-                let (best_sample, n_evals) = search::search_placement(
-                    &self.prob.layout,
-                    item,
-                    Some(pk),
-                    evaluator,
-                    self.sample_config,
-                    &mut self.rng,
-                );
-
-                let (new_dt, _eval) =
-                    best_sample.expect("search_placement should always return a sample");
+                let item_id = self.prob.layout().placed_items[pk].item_id;
+
+                //items which can be mirrored are searched in both variants, which share the sample budget
+                let variants = iter::once(item_id).chain(self.prob.mirror_twin(item_id)).collect_vec();
+                let sample_config = match variants.len() {
+                    1 => self.sample_config,
+                    _ => SampleConfig {
+                        n_container_samples: self.sample_config.n_container_samples / 2,
+                        n_focussed_samples: self.sample_config.n_focussed_samples / 2,
+                        ..self.sample_config
+                    },
+                };
+
+                let mut best: Option<(usize, DTransformation, SampleEval)> = None;
+                for variant_id in variants {
+                    let item = self.instance.item(variant_id);
+
+                    // create an evaluator to evaluate the samples during the search
+                    let evaluator = SeparationEvaluator::new(self.prob.layout(), item, pk, &self.ct)
+                        .with_proximity_groups(&proximity)
+                        .with_orientation_costs(self.prob.orientation_costs(variant_id));
+
+                    // search for a better position for the item
+                    let (best_sample, n_evals) = search::search_placement(
+                        self.prob.layout(),
+                        item,
+                        self.prob.rot_intervals(variant_id),
+                        self.ct.region(variant_id),
+                        Some(pk),
+                        evaluator,
+                        sample_config,
+                        &mut self.rng,
+                    );
+
+                    let (new_dt, eval) =
+                        best_sample.expect("search_placement should always return a sample");
+                    total_evals += n_evals;
+
+                    if best.is_none_or(|(_, _, best_eval)| eval < best_eval) {
+                        best = Some((variant_id, new_dt, eval));
+                    }
+                }
+                let (new_item_id, new_dt, _eval) = best.unwrap();
 
                 // move the item to the new position
-                self.move_item(pk, new_dt);
+                self.move_item(pk, new_item_id, new_dt);
                 total_moves += 1;
-                total_evals += n_evals;
             }
         }
         SepStats {
@@ -80,12 +106,9 @@ impl SeparatorWorker {
         }
     }
 
-    pub fn move_item(&mut self, pk: PItemKey, d_transf: DTransformation) -> PItemKey {
-        debug_assert!(tracker_matches_layout(&self.ct, &self.prob.layout));
-
-        let item = self
-            .instance
-            .item(self.prob.layout.placed_items[pk].item_id);
+    /// Moves the item to a new position, `item_id` is either the item itself or its mirrored twin
+    pub fn move_item(&mut self, pk: PItemKey, item_id: usize, d_transf: DTransformation) -> PItemKey {
+        debug_assert!(tracker_matches_layout(&self.ct, self.prob.layout()));
 
         let (old_l, old_w_l) = (self.ct.get_loss(pk), self.ct.get_weighted_loss(pk));
 
@@ -103,23 +126,21 @@ impl SeparatorWorker {
         );
 
         //modify the problem, by removing the item and placing it in the new position
-        let old_placement = self.prob.remove_item(pk);
-        let new_placement = SPPlacement {
-            d_transf,
-            item_id: item.id,
-        };
-        let new_pk = self.prob.place_item(new_placement);
+        let old_d_transf = self.prob.layout().placed_items[pk].d_transf;
+        self.prob.remove_item(pk);
+        let new_pk = self.prob.place_item(item_id, d_transf);
         //update the collision tracker to reflect the changes
-        self.ct.register_item_move(&self.prob.layout, pk, new_pk);
+        self.ct.register_item_move(self.prob.layout(), pk, new_pk);
 
         let (new_l, new_w_l) = (self.ct.get_loss(new_pk), self.ct.get_weighted_loss(new_pk));
 
         debug!(
-            "Moved {:?} (l: {}, wl: {}) to {:?} (l+1: {}, wl+1: {})",
-            old_placement,
+            "Moved item {} from [{}] (l: {}, wl: {}) to [{}] (l+1: {}, wl+1: {})",
+            item_id,
+            old_d_transf,
             FMT().fmt2(old_l),
             FMT().fmt2(old_w_l),
-            new_placement,
+            d_transf,
             FMT().fmt2(new_l),
             FMT().fmt2(new_w_l)
         );
@@ -129,7 +150,7 @@ impl SeparatorWorker {
             FMT().fmt2(old_w_l),
             FMT().fmt2(new_w_l)
         );
-        debug_assert!(tracker_matches_layout(&self.ct, &self.prob.layout));
+        debug_assert!(tracker_matches_layout(&self.ct, self.prob.layout()));
 
         new_pk
     }
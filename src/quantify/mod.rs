@@ -1,9 +1,12 @@
 use jagua_rs::geometry::geo_traits::DistanceTo;
 use jagua_rs::geometry::primitives::{Rect, SPolygon};
 use crate::consts::OVERLAP_PROXY_EPSILON_DIAM_RATIO;
-use crate::quantify::overlap_proxy::overlap_area_proxy;
+use crate::quantify::overlap_proxy::{overlap_area_proxy, overlap_area_proxy_static};
 
+pub mod nesting;
+pub mod orientation;
 pub mod overlap_proxy;
+pub mod proximity;
 mod pair_matrix;
 pub mod tracker;
 #[cfg(feature = "simd")]
@@ -49,4 +52,33 @@ pub fn quantify_collision_poly_container(s: &SPolygon, c_bbox: Rect) -> f32 {
     let penalty = calc_shape_penalty(s, s);
 
     2.0 * overlap.sqrt() * penalty
-}
\ No newline at end of file
+}
+
+/// The part of the container in which an item is allowed to be placed: its placement region (if any) within the container
+#[inline(always)]
+pub fn allowed_bbox(c_bbox: Rect, region: Option<Rect>) -> Rect {
+    region.and_then(|r| Rect::intersection(r, c_bbox)).unwrap_or(c_bbox)
+}
+
+/// Whether the shape sticks out of the bounding box
+#[inline(always)]
+pub fn exceeds_bbox(s: &SPolygon, bbox: Rect) -> bool {
+    let s_bbox = s.bbox;
+    s_bbox.x_min < bbox.x_min || s_bbox.y_min < bbox.y_min || s_bbox.x_max > bbox.x_max || s_bbox.y_max > bbox.y_max
+}
+/// Quantifies a collision between a simple polygon and a hole (defect) or a quality zone in the container.
+/// Follows [`quantify_collision_poly_poly`], with the hazard as a static shape: the poles of the polygon
+/// are measured against the exact boundary of the hazard, so thin or concave hazards are not overestimated.
+#[inline(always)]
+pub fn quantify_collision_poly_hole(s: &SPolygon, hole: &SPolygon) -> f32 {
+    let epsilon = f32::max(s.diameter, hole.diameter) * OVERLAP_PROXY_EPSILON_DIAM_RATIO;
+
+    let overlap_proxy = overlap_area_proxy_static(&s.surrogate(), hole, epsilon) + epsilon.powi(2);
+
+    debug_assert!(overlap_proxy.is_normal());
+
+    //hazards have no surrogate, the penalty only depends on the shape itself
+    let penalty = calc_shape_penalty(s, s);
+
+    overlap_proxy.sqrt() * penalty
+}
@@ -0,0 +1,54 @@
+use jagua_rs::geometry::DTransformation;
+use jagua_rs::geometry::geo_traits::{CollidesWith, Transformable};
+use jagua_rs::geometry::primitives::SPolygon;
+
+/// Holes of an item, empty if the item has none (or no item in the instance has holes)
+#[inline(always)]
+pub fn holes_of(item_holes: &[Vec<SPolygon>], item_id: usize) -> &[SPolygon] {
+    item_holes.get(item_id).map_or(&[], |h| h.as_slice())
+}
+
+/// Whether two overlapping shapes are nested (parts-in-parts): one lies entirely within a hole of the other.
+/// Nested shapes do not collide.
+/// The holes are defined in the reference frame of their item and are placed using the item's transformation.
+pub fn shapes_are_nested(
+    s1: &SPolygon,
+    holes1: &[SPolygon],
+    dt1: DTransformation,
+    s2: &SPolygon,
+    holes2: &[SPolygon],
+    dt2: DTransformation,
+) -> bool {
+    fits_in_holes(s2, holes1, dt1) || fits_in_holes(s1, holes2, dt2)
+}
+
+/// Whether the shape lies entirely within one of the holes, placed with `dt`
+pub fn fits_in_holes(s: &SPolygon, holes: &[SPolygon], dt: DTransformation) -> bool {
+    if holes.is_empty() {
+        return false;
+    }
+    let t = dt.compose();
+    holes.iter().any(|hole| {
+        let hole = hole.transform_clone(&t);
+        let (h_bbox, s_bbox) = (hole.bbox, s.bbox);
+        let bbox_contained = h_bbox.x_min <= s_bbox.x_min
+            && h_bbox.y_min <= s_bbox.y_min
+            && h_bbox.x_max >= s_bbox.x_max
+            && h_bbox.y_max >= s_bbox.y_max;
+        //all vertices of the shape inside the hole and none of the hole inside the shape
+        bbox_contained
+            && s.vertices.iter().all(|v| hole.collides_with(v))
+            && !hole.vertices.iter().any(|v| s.collides_with(v))
+            && !edges_intersect(s, &hole)
+    })
+}
+
+/// Whether any edge of the first shape intersects an edge of the second.
+/// Needed on top of the vertex checks: a thin notch of a concave hole can cross the shape without any vertex inside it.
+fn edges_intersect(s1: &SPolygon, s2: &SPolygon) -> bool {
+    (0..s1.n_vertices()).map(|i| s1.edge(i)).any(|e1| {
+        (0..s2.n_vertices())
+            .map(|j| s2.edge(j))
+            .any(|e2| e1.collides_with(&e2))
+    })
+}
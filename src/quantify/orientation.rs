@@ -0,0 +1,28 @@
+use crate::consts::{ORIENTATION_MATCH_TOLERANCE, ORIENTATION_PENALTY_WEIGHT};
+use jagua_rs::entities::Item;
+use jagua_rs::geometry::normalize_rotation;
+use jagua_rs::probs::spp::entities::SPSolution;
+
+/// Cost of placing an item with rotation `r`: the cost of the disfavored orientation (in radians) it matches, if any
+pub fn orientation_cost(costs: &[(f32, f32)], r: f32) -> f32 {
+    costs
+        .iter()
+        .find(|(o, _)| normalize_rotation(r - o).abs() < ORIENTATION_MATCH_TOLERANCE)
+        .map_or(0.0, |(_, cost)| *cost)
+}
+
+/// Quantifies the cost of an orientation as a penalty proportional to the (convex hull) area of the item
+#[inline(always)]
+pub fn quantify_orientation_penalty(item: &Item, cost: f32) -> f32 {
+    ORIENTATION_PENALTY_WEIGHT * cost * item.shape_cd.surrogate().convex_hull_area
+}
+
+/// Total orientation cost of all placed items of a solution
+pub fn solution_orientation_cost(solution: &SPSolution, item_costs: &[Vec<(f32, f32)>]) -> f32 {
+    solution
+        .layout_snapshot
+        .placed_items
+        .values()
+        .filter_map(|pi| Some(orientation_cost(item_costs.get(pi.item_id)?, pi.d_transf.rotation())))
+        .sum()
+}
@@ -1,6 +1,8 @@
 use std::f32::consts::PI;
 use jagua_rs::geometry::fail_fast::SPSurrogate;
-use jagua_rs::geometry::geo_traits::DistanceTo;
+use jagua_rs::geometry::geo_enums::GeoPosition;
+use jagua_rs::geometry::geo_traits::{DistanceTo, SeparationDistance};
+use jagua_rs::geometry::primitives::SPolygon;
 
 /// Calculates a proxy for the overlap area between two simple polygons (using poles).
 #[inline(always)]
@@ -23,4 +25,29 @@ pub fn overlap_area_proxy<'a>(sp1: &SPSurrogate, sp2: &SPSurrogate, epsilon: f32
     debug_assert!(total_overlap.is_normal());
     
     total_overlap
-}
\ No newline at end of file
+}
+/// Calculates a proxy for the overlap area between a simple polygon (using its poles) and a static shape.
+/// Static shapes (holes, quality zones) have no poles of their own, so the penetration depth of every pole
+/// is measured against the exact boundary of the shape instead.
+#[inline(always)]
+pub fn overlap_area_proxy_static(sp: &SPSurrogate, shape: &SPolygon, epsilon: f32) -> f32 {
+    let mut total_overlap = 0.0;
+    for p in &sp.poles {
+        // Penetration depth of the pole (circle) into the shape
+        let pd = match shape.separation_distance(&p.center) {
+            (GeoPosition::Interior, d) => p.radius + d,
+            (GeoPosition::Exterior, d) => p.radius - d,
+        };
+
+        let pd_decay = match pd >= epsilon {
+            true => pd,
+            false => epsilon.powi(2) / (-pd + 2.0 * epsilon),
+        };
+
+        total_overlap += pd_decay * p.radius;
+    }
+    total_overlap *= PI;
+    debug_assert!(total_overlap.is_normal());
+
+    total_overlap
+}
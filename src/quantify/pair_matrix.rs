@@ -17,6 +17,25 @@ impl PairMatrix {
             data: vec![CTEntry { weight: 1.0, loss: 0.0 }; len],
         }
     }
+
+    /// Adds a row (and column) at the end of the matrix, the existing entries are kept in place
+    pub fn push(&mut self) {
+        self.size += 1;
+        self.data.resize(self.size * (self.size + 1) / 2, CTEntry { weight: 1.0, loss: 0.0 });
+    }
+
+    /// Removes a row (and column) by replacing it with the last one, like [`Vec::swap_remove`]
+    pub fn swap_remove(&mut self, idx: usize) {
+        let last = self.size - 1;
+        if idx != last {
+            for i in (0..last).filter(|&i| i != idx) {
+                self[(idx, i)] = self[(last, i)];
+            }
+            self[(idx, idx)] = self[(last, last)];
+        }
+        self.size = last;
+        self.data.truncate(self.size * (self.size + 1) / 2);
+    }
 }
 
 impl Index<(usize, usize)> for PairMatrix {
@@ -34,16 +53,16 @@ impl IndexMut<(usize, usize)> for PairMatrix {
 }
 
 fn calc_idx(row: usize, col: usize, size: usize) -> usize {
-    /* Example:
-        0 1 2 3
-          4 5 6
-            7 8
-              9
+    /* Example (stored row by row, so rows can be added and removed at the end):
+        0
+        1 2
+        3 4 5
+        6 7 8 9
     */
     debug_assert!(row < size && col < size);
-    if row <= col {
-        (row * size) + col - ((row * (row + 1)) / 2)
+    if row >= col {
+        (row * (row + 1)) / 2 + col
     } else {
-        (col * size) + row - ((col * (col + 1)) / 2)
+        (col * (col + 1)) / 2 + row
     }
 }
@@ -0,0 +1,48 @@
+use crate::consts::PROXIMITY_PENALTY_WEIGHT;
+use crate::quantify::calc_shape_penalty;
+use jagua_rs::geometry::primitives::SPolygon;
+
+/// Groups of items which should be placed close to each other, e.g. pieces of the same garment to avoid shade differences.
+/// This is a soft constraint: the x-distance between two items of the same group should not exceed the maximum distance of the group.
+#[derive(Clone, Debug, Default)]
+pub struct ProximityGroups {
+    /// For every item, the group it belongs to (if any)
+    pub group_of: Vec<Option<usize>>,
+    /// For every group, the maximum x-distance between its items
+    pub max_dists: Vec<f32>,
+}
+
+impl ProximityGroups {
+    pub fn is_empty(&self) -> bool {
+        self.max_dists.is_empty()
+    }
+
+    pub fn group(&self, item_id: usize) -> Option<usize> {
+        self.group_of.get(item_id).copied().flatten()
+    }
+
+    /// Maximum x-distance between both items, if they belong to the same group
+    pub fn max_dist(&self, item_id_1: usize, item_id_2: usize) -> Option<f32> {
+        match (self.group(item_id_1), self.group(item_id_2)) {
+            (Some(g1), Some(g2)) if g1 == g2 => Some(self.max_dists[g1]),
+            _ => None,
+        }
+    }
+}
+
+/// Distance along the x-axis between the bounding boxes of both shapes, zero if they overlap along the x-axis
+pub fn x_distance(s1: &SPolygon, s2: &SPolygon) -> f32 {
+    let (b1, b2) = (s1.bbox, s2.bbox);
+    (f32::max(b1.x_min, b2.x_min) - f32::min(b1.x_max, b2.x_max)).max(0.0)
+}
+
+/// Quantifies how much two shapes of the same group exceed the maximum x-distance between them.
+/// The distance is measured between the bounding boxes of both shapes (see [`x_distance`]), not between the shapes themselves.
+#[inline(always)]
+pub fn quantify_proximity_violation(s1: &SPolygon, s2: &SPolygon, max_dist: f32) -> f32 {
+    let excess = x_distance(s1, s2) - max_dist;
+    match excess > 0.0 {
+        true => PROXIMITY_PENALTY_WEIGHT * excess * calc_shape_penalty(s1, s2),
+        false => 0.0,
+    }
+}
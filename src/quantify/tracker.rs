@@ -1,15 +1,22 @@
 use jagua_rs::collision_detection::hazards::HazardEntity;
 use jagua_rs::collision_detection::hazards::collector::{BasicHazardCollector, HazardCollector};
 
+use crate::eval::hazard_filter::hazard_is_relevant;
 use crate::consts::{GLS_WEIGHT_DECAY, GLS_WEIGHT_MAX_INC_RATIO, GLS_WEIGHT_MIN_INC_RATIO};
+use crate::quantify::nesting::{holes_of, shapes_are_nested};
 use crate::quantify::pair_matrix::PairMatrix;
-use crate::quantify::{quantify_collision_poly_container, quantify_collision_poly_poly};
+use crate::quantify::{
+    allowed_bbox, exceeds_bbox, quantify_collision_poly_container, quantify_collision_poly_hole,
+    quantify_collision_poly_poly,
+};
 use crate::util::assertions::tracker_matches_layout;
 use jagua_rs::entities::{Layout, PItemKey};
+use jagua_rs::geometry::primitives::{Rect, SPolygon};
 use ordered_float::Float;
 use slotmap::SecondaryMap;
+use std::sync::Arc;
 
-/// Tracker of both collisions between pair of items and collisions with the container.
+/// Tracker of both collisions between pair of items and collisions with the container (exterior and holes).
 /// It also stores the weights for every pair of hazards and is used as a cache for collisions.
 #[derive(Debug, Clone)]
 pub struct CollisionTracker {
@@ -17,12 +24,23 @@ pub struct CollisionTracker {
     pub pk_idx_map: SecondaryMap<PItemKey, usize>,
     pub pair_collisions: PairMatrix,
     pub container_collisions: Vec<CTEntry>,
+    /// Collisions of every item with the forbidden zones of the container, combined over all zones.
+    /// These are the holes (defects) and the quality zones of insufficient quality for the item.
+    pub hole_collisions: Vec<CTEntry>,
+    /// Holes of every item (in the reference frame of the item), other items nested in them do not collide
+    pub item_holes: Arc<[Vec<SPolygon>]>,
+    /// Region of the container every item is restricted to (if any), leaving it counts as a container collision
+    pub item_regions: Arc<[Option<Rect>]>,
 }
 
 pub type CTSnapshot = CollisionTracker;
 
 impl CollisionTracker {
-    pub fn new(l: &Layout) -> Self {
+    pub fn new(
+        l: &Layout,
+        item_holes: Arc<[Vec<SPolygon>]>,
+        item_regions: Arc<[Option<Rect>]>,
+    ) -> Self {
         let size = l.placed_items.len();
 
         // Create the tracker
@@ -42,6 +60,15 @@ impl CollisionTracker {
                 };
                 size
             ],
+            hole_collisions: vec![
+                CTEntry {
+                    weight: 1.0,
+                    loss: 0.0
+                };
+                size
+            ],
+            item_holes,
+            item_regions,
         };
 
         // Recompute the loss for all items
@@ -64,6 +91,7 @@ impl CollisionTracker {
             self.pair_collisions[(idx, i)].loss = 0.0;
         }
         self.container_collisions[idx].loss = 0.0;
+        self.hole_collisions[idx].loss = 0.0;
 
         // Compute which hazards are currently colliding with the item
         let mut collector = BasicHazardCollector::with_capacity(l.placed_items.len() + 1);
@@ -71,9 +99,14 @@ impl CollisionTracker {
         // Remove the item itself from the detector
         collector.remove_by_entity(&HazardEntity::from((pk, pi)));
 
+        let c_bbox = self.allowed_bbox(l, pi.item_id);
+
         // For each colliding hazard, quantify the collision and store it in the tracker
-        for (_, haz) in collector.iter() {
+        for (hkey, haz) in collector.iter() {
             match haz {
+                HazardEntity::PlacedItem { pk: other_pk, .. } if self.items_are_nested(l, pk, *other_pk) => {
+                    //nested in a hole of the other item (or vice versa), no collision
+                }
                 HazardEntity::PlacedItem { pk: other_pk, .. } => {
                     let shape_other = &l.placed_items[*other_pk].shape;
                     let idx_other = self.pk_idx_map[*other_pk];
@@ -83,13 +116,59 @@ impl CollisionTracker {
                     self.pair_collisions[(idx, idx_other)].loss = loss;
                 }
                 HazardEntity::Exterior => {
-                    let loss = quantify_collision_poly_container(shape, l.container.outer_cd.bbox);
+                    let loss = quantify_collision_poly_container(shape, c_bbox);
                     assert!(loss > 0.0, "loss for a collision should be > 0.0");
                     self.container_collisions[idx].loss = loss;
                 }
+                HazardEntity::Hole { .. } => {
+                    let shape_hole = &l.cde().hazards_map[hkey].shape;
+
+                    let loss = quantify_collision_poly_hole(shape, shape_hole);
+                    assert!(loss > 0.0, "loss for a collision should be > 0.0");
+                    self.hole_collisions[idx].loss += loss;
+                }
+                HazardEntity::InferiorQualityZone { .. } => {
+                    if hazard_is_relevant(haz, pi.hazard_filter.as_ref()) {
+                        let shape_zone = &l.cde().hazards_map[hkey].shape;
+
+                        let loss = quantify_collision_poly_hole(shape, shape_zone);
+                        assert!(loss > 0.0, "loss for a collision should be > 0.0");
+                        self.hole_collisions[idx].loss += loss;
+                    }
+                }
                 _ => unimplemented!("unsupported hazard entity"),
             }
         }
+
+        // Leaving its region is a collision with the container, even if the item is still within the strip
+        if self.region(pi.item_id).is_some() && exceeds_bbox(shape, c_bbox) {
+            self.container_collisions[idx].loss = quantify_collision_poly_container(shape, c_bbox);
+        }
+    }
+
+    pub fn region(&self, item_id: usize) -> Option<Rect> {
+        self.item_regions.get(item_id).copied().flatten()
+    }
+
+    /// The part of the container the item is allowed to be placed in
+    pub fn allowed_bbox(&self, l: &Layout, item_id: usize) -> Rect {
+        allowed_bbox(l.container.outer_cd.bbox, self.region(item_id))
+    }
+
+    /// Whether one of the two placed items lies entirely within a hole of the other
+    pub fn items_are_nested(&self, l: &Layout, pk1: PItemKey, pk2: PItemKey) -> bool {
+        if self.item_holes.is_empty() {
+            return false;
+        }
+        let (pi1, pi2) = (&l.placed_items[pk1], &l.placed_items[pk2]);
+        shapes_are_nested(
+            &pi1.shape,
+            holes_of(&self.item_holes, pi1.item_id),
+            pi1.d_transf,
+            &pi2.shape,
+            holes_of(&self.item_holes, pi2.item_id),
+            pi2.d_transf,
+        )
     }
 
     pub fn restore_but_keep_weights(&mut self, cts: &CTSnapshot, layout: &Layout) {
@@ -104,6 +183,10 @@ impl CollisionTracker {
             .iter_mut()
             .zip(cts.container_collisions.iter())
             .for_each(|(a, b)| a.loss = b.loss);
+        self.hole_collisions
+            .iter_mut()
+            .zip(cts.hole_collisions.iter())
+            .for_each(|(a, b)| a.loss = b.loss);
         debug_assert!(tracker_matches_layout(self, layout));
     }
 
@@ -121,12 +204,45 @@ impl CollisionTracker {
         debug_assert!(tracker_matches_layout(self, l));
     }
 
+    /// Registers an item which was added to the layout, the weights of all other items are kept
+    pub fn register_item_insert(&mut self, l: &Layout, pk: PItemKey) {
+        let idx = self.size;
+        self.pk_idx_map.insert(pk, idx);
+        self.size += 1;
+        self.pair_collisions.push();
+        self.container_collisions.push(CTEntry { weight: 1.0, loss: 0.0 });
+        self.hole_collisions.push(CTEntry { weight: 1.0, loss: 0.0 });
+
+        self.recompute_loss_for_item(pk, l);
+
+        debug_assert!(tracker_matches_layout(self, l));
+    }
+
+    /// Registers an item which was removed from the layout, the weights of all other items are kept
+    pub fn register_item_removal(&mut self, l: &Layout, pk: PItemKey) {
+        let idx = self.pk_idx_map.remove(pk).unwrap();
+        let last = self.size - 1;
+
+        //the last item takes over the index of the removed one
+        let last_pk = self.pk_idx_map.iter().find(|(_, i)| **i == last).map(|(k, _)| k);
+        if let Some(last_pk) = last_pk {
+            self.pk_idx_map[last_pk] = idx;
+        }
+        self.pair_collisions.swap_remove(idx);
+        self.container_collisions.swap_remove(idx);
+        self.hole_collisions.swap_remove(idx);
+        self.size = last;
+
+        debug_assert!(tracker_matches_layout(self, l));
+    }
+
     pub fn increment_weights(&mut self) {
         let max_loss = self
             .pair_collisions
             .data
             .iter()
             .chain(self.container_collisions.iter())
+            .chain(self.hole_collisions.iter())
             .map(|e| e.loss)
             .fold(0.0, |a, b| a.max(b));
 
@@ -135,6 +251,7 @@ impl CollisionTracker {
             .data
             .iter_mut()
             .chain(self.container_collisions.iter_mut())
+            .chain(self.hole_collisions.iter_mut())
         {
             let multiplier = match e.loss == 0.0 {
                 true => GLS_WEIGHT_DECAY, // no collision
@@ -158,6 +275,11 @@ impl CollisionTracker {
         self.container_collisions[idx].weight
     }
 
+    pub fn get_hole_weight(&self, pk: PItemKey) -> f32 {
+        let idx = self.pk_idx_map[pk];
+        self.hole_collisions[idx].weight
+    }
+
     pub fn get_pair_loss(&self, pk1: PItemKey, pk2: PItemKey) -> f32 {
         let (idx1, idx2) = (self.pk_idx_map[pk1], self.pk_idx_map[pk2]);
         self.pair_collisions[(idx1, idx2)].loss
@@ -168,6 +290,11 @@ impl CollisionTracker {
         self.container_collisions[idx].loss
     }
 
+    pub fn get_hole_loss(&self, pk: PItemKey) -> f32 {
+        let idx = self.pk_idx_map[pk];
+        self.hole_collisions[idx].loss
+    }
+
     pub fn get_loss(&self, pk: PItemKey) -> f32 {
         let idx = self.pk_idx_map[pk];
 
@@ -175,7 +302,7 @@ impl CollisionTracker {
             .map(|i| self.pair_collisions[(idx, i)].loss)
             .sum::<f32>();
 
-        self.container_collisions[idx].loss + pair_loss
+        self.container_collisions[idx].loss + self.hole_collisions[idx].loss + pair_loss
     }
 
     pub fn get_weighted_loss(&self, pk: PItemKey) -> f32 {
@@ -185,13 +312,16 @@ impl CollisionTracker {
             .map(|i| self.pair_collisions[(idx, i)].weighted_loss())
             .sum::<f32>();
 
-        self.container_collisions[idx].weighted_loss() + w_pair_loss
+        self.container_collisions[idx].weighted_loss()
+            + self.hole_collisions[idx].weighted_loss()
+            + w_pair_loss
     }
 
     pub fn get_total_loss(&self) -> f32 {
         let cont_o = self
             .container_collisions
             .iter()
+            .chain(self.hole_collisions.iter())
             .map(|e| e.loss)
             .sum::<f32>();
 
@@ -209,6 +339,7 @@ impl CollisionTracker {
         let cont_w_o = self
             .container_collisions
             .iter()
+            .chain(self.hole_collisions.iter())
             .map(|e| e.weighted_loss())
             .sum::<f32>();
 
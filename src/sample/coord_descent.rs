@@ -1,5 +1,6 @@
 use crate::consts::{CD_STEP_FAIL, CD_STEP_SUCCESS};
 use crate::eval::sample_eval::{SampleEval, SampleEvaluator};
+use crate::sample::rot_intervals::RotIntervals;
 use jagua_rs::geometry::DTransformation;
 use log::trace;
 use rand::Rng;
@@ -21,10 +22,12 @@ pub struct CDConfig {
 }
 
 /// Refines an initial 'sample' (transformation and evaluation) into a local minimum using a coordinate descent inspired algorithm.
+/// If the item has rotation intervals, the wiggle axis is restricted to them.
 pub fn refine_coord_desc(
     (init_dt, init_eval): (DTransformation, SampleEval),
     evaluator: &mut impl SampleEvaluator,
     cd_config: CDConfig,
+    rot_intervals: Option<&RotIntervals>,
     rng: &mut impl Rng,
 ) -> (DTransformation, SampleEval) {
     let n_evals_init = evaluator.n_evals();
@@ -40,6 +43,7 @@ pub fn refine_coord_desc(
         r_step: cd_config.r_step_init,
         r_step_limit: cd_config.r_step_limit,
         wiggle: cd_config.wiggle,
+        rot_intervals,
     };
 
     // From the CD state, ask for candidate positions to evaluate. If none provided, stop.
@@ -62,7 +66,7 @@ pub fn refine_coord_desc(
 }
 
 #[derive(Debug)]
-struct CoordinateDescent {
+struct CoordinateDescent<'a> {
     /// The current position in the coordinate descent
     pub pos: DTransformation,
     /// The current evaluation of the position
@@ -79,9 +83,11 @@ struct CoordinateDescent {
     pub r_step_limit: f32,
     /// Defines whether the wiggle axis is enabled
     pub wiggle: bool,
+    /// Intervals to which the wiggle axis is restricted, if any
+    pub rot_intervals: Option<&'a RotIntervals>,
 }
 
-impl CoordinateDescent {
+impl CoordinateDescent<'_> {
 
     /// Generates candidates to be evaluated. 
     pub fn ask(&self) -> Option<[DTransformation; 2]> {
@@ -104,6 +110,7 @@ impl CoordinateDescent {
             };
             
             let c = transformations.map(|(tx, ty, r)| {
+                let r = self.rot_intervals.map_or(r, |ri| ri.closest(r));
                 DTransformation::new(r, (tx, ty))
             });
             
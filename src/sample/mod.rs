@@ -1,4 +1,5 @@
 mod best_samples;
 mod coord_descent;
+pub mod rot_intervals;
 pub mod search;
 pub mod uniform_sampler;
\ No newline at end of file
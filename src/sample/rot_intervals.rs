@@ -0,0 +1,59 @@
+use jagua_rs::geometry::normalize_rotation;
+use ordered_float::OrderedFloat;
+use std::f32::consts::PI;
+
+/// Intervals of allowed rotations (in radians), e.g. to keep fabric parts within a few degrees of their grain line.
+/// jagua-rs only supports discrete or fully continuous rotations: items with rotation intervals
+/// are continuous in jagua-rs and the intervals are enforced by the samplers and the coordinate descent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RotIntervals(Vec<(f32, f32)>);
+
+impl RotIntervals {
+    pub fn new(intervals: Vec<(f32, f32)>) -> Self {
+        assert!(!intervals.is_empty(), "at least one rotation interval is required");
+        assert!(intervals.iter().all(|(s, e)| s <= e), "rotation intervals should be ascending");
+        Self(intervals)
+    }
+
+    pub fn intervals(&self) -> &[(f32, f32)] {
+        &self.0
+    }
+
+    /// Whether the rotation lies within one of the intervals
+    pub fn contains(&self, r: f32) -> bool {
+        self.0.iter().any(|&(s, e)| (r - s).rem_euclid(2.0 * PI) <= e - s)
+    }
+
+    /// The allowed rotation closest to `r`
+    pub fn closest(&self, r: f32) -> f32 {
+        match self.contains(r) {
+            true => r,
+            false => self
+                .0
+                .iter()
+                .flat_map(|&(s, e)| [s, e])
+                .min_by_key(|&b| OrderedFloat(normalize_rotation(r - b).abs()))
+                .unwrap(),
+        }
+    }
+
+    /// About `n` rotations spread evenly over the intervals, at least one per interval
+    pub fn spread(&self, n: usize) -> Vec<f32> {
+        let total_length = self.0.iter().map(|(s, e)| e - s).sum::<f32>();
+        self.0
+            .iter()
+            .flat_map(|&(s, e)| {
+                let n_interval = match total_length > 0.0 {
+                    true => ((n as f32 * (e - s) / total_length).round() as usize).max(1),
+                    false => 1,
+                };
+                match n_interval {
+                    1 => vec![(s + e) / 2.0],
+                    _ => (0..n_interval)
+                        .map(|i| s + (e - s) * i as f32 / (n_interval - 1) as f32)
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+}
@@ -1,10 +1,13 @@
 use jagua_rs::entities::{Item, Layout, PItemKey};
 use jagua_rs::geometry::DTransformation;
 use jagua_rs::geometry::geo_enums::RotationRange;
+use jagua_rs::geometry::primitives::Rect;
 use crate::consts::{SND_REFINE_CD_TL_RATIOS, PRE_REFINE_CD_TL_RATIOS, UNIQUE_SAMPLE_THRESHOLD, PRE_REFINE_CD_R_STEPS, SND_REFINE_CD_R_STEPS};
 use crate::eval::sample_eval::{SampleEval, SampleEvaluator};
 use crate::sample::best_samples::BestSamples;
 use crate::sample::coord_descent::{refine_coord_desc, CDConfig};
+use crate::quantify::allowed_bbox;
+use crate::sample::rot_intervals::RotIntervals;
 use crate::sample::uniform_sampler::UniformBBoxSampler;
 use log::debug;
 use rand::Rng;
@@ -16,8 +19,10 @@ pub struct SampleConfig {
     pub n_coord_descents: usize,
 }
 
-pub fn search_placement(l: &Layout, item: &Item, ref_pk: Option<PItemKey>, mut evaluator: impl SampleEvaluator, sample_config: SampleConfig, rng: &mut impl Rng) -> (Option<(DTransformation, SampleEval)>, usize) {
+pub fn search_placement(l: &Layout, item: &Item, rot_intervals: Option<&RotIntervals>, region: Option<Rect>, ref_pk: Option<PItemKey>, mut evaluator: impl SampleEvaluator, sample_config: SampleConfig, rng: &mut impl Rng) -> (Option<(DTransformation, SampleEval)>, usize) {
     let item_min_dim = f32::min(item.shape_cd.bbox.width(), item.shape_cd.bbox.height());
+    //only sample within the placement region of the item
+    let c_bbox = allowed_bbox(l.container.outer_cd.bbox, region);
 
     let mut best_samples = BestSamples::new(sample_config.n_coord_descents, item_min_dim * UNIQUE_SAMPLE_THRESHOLD);
 
@@ -32,7 +37,7 @@ pub fn search_placement(l: &Layout, item: &Item, ref_pk: Option<PItemKey>, mut e
 
             //create a sampler around the current placement
             let pi_bbox = l.placed_items[ref_pk].shape.bbox;
-            UniformBBoxSampler::new(pi_bbox, item, l.container.outer_cd.bbox)
+            UniformBBoxSampler::new(pi_bbox, item, c_bbox, rot_intervals)
         }
         None => None,
     };
@@ -45,7 +50,7 @@ pub fn search_placement(l: &Layout, item: &Item, ref_pk: Option<PItemKey>, mut e
         }
     }
 
-    let container_sampler = UniformBBoxSampler::new(l.container.outer_cd.bbox, item, l.container.outer_cd.bbox);
+    let container_sampler = UniformBBoxSampler::new(c_bbox, item, c_bbox, rot_intervals);
 
     if let Some(container_sampler) = container_sampler {
         for _ in 0..sample_config.n_container_samples {
@@ -61,6 +66,7 @@ pub fn search_placement(l: &Layout, item: &Item, ref_pk: Option<PItemKey>, mut e
             start.clone(),
             &mut evaluator,
             prerefine_cd_config(item),
+            rot_intervals,
             rng,
         );
         best_samples.report(descended.0, descended.1);
@@ -73,6 +79,7 @@ pub fn search_placement(l: &Layout, item: &Item, ref_pk: Option<PItemKey>, mut e
             s, 
             &mut evaluator, 
             final_refine_cd_config(item), 
+            rot_intervals,
             rng,
         )
     );
@@ -10,6 +10,7 @@ use jagua_rs::geometry::primitives::Rect;
 use jagua_rs::geometry::{normalize_rotation, DTransformation, Transformation};
 use ndarray::Array;
 use ordered_float::{OrderedFloat};
+use crate::sample::rot_intervals::RotIntervals;
 
 const ROT_N_SAMPLES: usize = 16; // number of rotations to sample for continuous rotation
 
@@ -28,11 +29,13 @@ struct RotEntry {
 }
 
 impl UniformBBoxSampler {
-    pub fn new(sample_bbox: Rect, item: &Item, container_bbox: Rect) -> Option<Self> {
-        let rotations = match &item.allowed_rotation {
-            RotationRange::None => &vec![0.0],
-            RotationRange::Discrete(r) => r,
-            RotationRange::Continuous => {
+    /// Rotation intervals of the item (if any) take precedence over its allowed rotations in jagua-rs.
+    pub fn new(sample_bbox: Rect, item: &Item, container_bbox: Rect, rot_intervals: Option<&RotIntervals>) -> Option<Self> {
+        let rotations = match (&item.allowed_rotation, rot_intervals) {
+            (_, Some(ri)) => &ri.spread(ROT_N_SAMPLES),
+            (RotationRange::None, None) => &vec![0.0],
+            (RotationRange::Discrete(r), None) => r,
+            (RotationRange::Continuous, None) => {
                 // for continuous rotation, we sample a set of rotations spaced evenly
                 &Array::linspace(0.0, 2.0 * PI, ROT_N_SAMPLES).to_vec()
             }
@@ -91,10 +94,11 @@ fn intersect_range(a: &Range<f32>, b: &Range<f32>) -> Range<f32> {
 }
 
 /// Converts a sample transformation to the closest feasible transformation. (for now just mapping rotation to the closest allowed one)
-pub fn convert_sample_to_closest_feasible(dt: DTransformation, item: &Item) -> DTransformation {
-    let feasible_rotation = match &item.allowed_rotation {
-        RotationRange::None => 0.0,
-        RotationRange::Discrete(v) => {
+pub fn convert_sample_to_closest_feasible(dt: DTransformation, item: &Item, rot_intervals: Option<&RotIntervals>) -> DTransformation {
+    let feasible_rotation = match (&item.allowed_rotation, rot_intervals) {
+        (_, Some(ri)) => ri.closest(dt.rotation()),
+        (RotationRange::None, None) => 0.0,
+        (RotationRange::Discrete(v), None) => {
             // find the closest rotation in the discrete set
             v.iter().min_by_key(|&&r| {
                 // make sure to normalize the delta to the range [-PI, PI]
@@ -102,7 +106,7 @@ pub fn convert_sample_to_closest_feasible(dt: DTransformation, item: &Item) -> D
                 OrderedFloat(norm_delta.abs())
             }).cloned().unwrap()
         }
-        RotationRange::Continuous => {
+        (RotationRange::Continuous, None) => {
             // for continuous rotation, we can just use the sample rotation
             dt.rotation()
         }
@@ -1,6 +1,7 @@
+use crate::eval::hazard_filter::hazard_is_relevant;
 use crate::eval::specialized_jaguars_pipeline::SpecializedHazardCollector;
 use crate::quantify::tracker::CollisionTracker;
-use crate::quantify::{quantify_collision_poly_container, quantify_collision_poly_poly};
+use crate::quantify::{exceeds_bbox, quantify_collision_poly_container, quantify_collision_poly_hole, quantify_collision_poly_poly};
 use float_cmp::{approx_eq, assert_approx_eq};
 use itertools::Itertools;
 use jagua_rs::util::assertions;
@@ -24,6 +25,11 @@ pub fn tracker_matches_layout(ct: &CollisionTracker, l: &Layout) -> bool {
         assert_eq!(ct.get_pair_loss(pk1, pk1), 0.0);
         for (pk2, pi2) in l.placed_items.iter().filter(|(k, _)| *k != pk1) {
             let stored_loss = ct.get_pair_loss(pk1, pk2);
+            if ct.items_are_nested(l, pk1, pk2) {
+                //nested items overlap, but do not collide
+                assert_eq!(stored_loss, 0.0);
+                continue;
+            }
             match collector.iter().any(|(_, he)| he == &HazardEntity::from((pk2, pi2))) {
                 true => {
                     let calc_loss = quantify_collision_poly_poly(&pi1.shape, &pi2.shape);
@@ -116,13 +122,25 @@ pub fn tracker_matches_layout(ct: &CollisionTracker, l: &Layout) -> bool {
                 }
             }
         }
-        if collector.contains_entity(&HazardEntity::Exterior) {
+        let c_bbox = ct.allowed_bbox(l, pi1.item_id);
+        let outside_region = ct.region(pi1.item_id).is_some() && exceeds_bbox(&pi1.shape, c_bbox);
+        if collector.contains_entity(&HazardEntity::Exterior) || outside_region {
             let stored_loss = ct.get_container_loss(pk1);
-            let calc_loss = quantify_collision_poly_container(&pi1.shape, l.container.outer_cd.bbox);
+            let calc_loss = quantify_collision_poly_container(&pi1.shape, c_bbox);
             assert_approx_eq!(f32, stored_loss, calc_loss, ulps = 5);
         } else {
             assert_eq!(ct.get_container_loss(pk1), 0.0);
         }
+        let calc_hole_loss = collector
+            .iter()
+            .filter(|(_, he)| match he {
+                HazardEntity::Hole { .. } => true,
+                HazardEntity::InferiorQualityZone { .. } => hazard_is_relevant(he, pi1.hazard_filter.as_ref()),
+                _ => false,
+            })
+            .map(|(hkey, _)| quantify_collision_poly_hole(&pi1.shape, &l.cde().hazards_map[hkey].shape))
+            .sum::<f32>();
+        assert_approx_eq!(f32, ct.get_hole_loss(pk1), calc_hole_loss, ulps = 5);
     }
 
     true
@@ -143,7 +161,14 @@ pub fn custom_pipeline_matches_jaguars(shape: &SPolygon, det: &SpecializedHazard
     };
 
     //make sure these detection maps are equivalent
-    let default_set: HashSet<HazardEntity> = base_detector.entities().cloned().collect();
+    let mut default_set: HashSet<HazardEntity> = base_detector.entities()
+        .filter(|he| hazard_is_relevant(he, det.qz_filter))
+        .filter(|he| !matches!(he, HazardEntity::PlacedItem { pk, .. } if det.is_nested(*pk, shape)))
+        .cloned().collect();
+    //jagua-rs is unaware of the placement regions of the items
+    if det.region.is_some_and(|(_, r)| exceeds_bbox(shape, r)) {
+        default_set.insert(HazardEntity::Exterior);
+    }
     let custom_set: HashSet<HazardEntity> = det.entities().cloned().collect();
 
     assert_eq!(default_set, custom_set, "custom cde pipeline does not match jagua-rs! for pk: {:?}", det.current_pk);
@@ -0,0 +1,113 @@
+use crate::consts::CLEARANCE_VERIFICATION_TOLERANCE;
+use crate::optimizer::problem::ItemFeatures;
+use crate::quantify::nesting::{holes_of, shapes_are_nested};
+use crate::util::io::ExtClearanceViolation;
+use itertools::Itertools;
+use jagua_rs::entities::{Instance, LayoutSnapshot};
+use jagua_rs::geometry::geo_traits::{CollidesWith, DistanceTo, Transformable};
+use jagua_rs::geometry::primitives::SPolygon;
+use log::{info, warn};
+
+/// Verifies the clearances of a layout on the original (non-inflated) shapes of the items:
+/// the distance between every pair of items should be at least the mean of their clearances and
+/// the distance to the edges of the container's bounding box at least the border margin.
+/// Logs and returns every violation, referring to the original items.
+pub fn verify_clearances(
+    instance: &impl Instance,
+    layout: &LayoutSnapshot,
+    item_features: &ItemFeatures,
+    border_margin: f32,
+) -> Vec<ExtClearanceViolation> {
+    let placed = layout
+        .placed_items
+        .values()
+        .map(|pi| {
+            let orig = &instance.item(pi.item_id).shape_orig;
+            let shape = orig
+                .shape
+                .transform_clone(&orig.pre_transform.compose())
+                .transform_clone(&pi.d_transf.compose());
+            let clearance = item_features.clearances.get(pi.item_id).copied().unwrap_or(0.0);
+            (pi, shape, clearance)
+        })
+        .collect_vec();
+
+    let bounds = layout.container.outer_orig.bbox;
+    let mut violations = vec![];
+
+    for (pi, shape, _) in placed.iter() {
+        let bbox = shape.bbox;
+        let edge_dist = [
+            bbox.x_min - bounds.x_min,
+            bbox.y_min - bounds.y_min,
+            bounds.x_max - bbox.x_max,
+            bounds.y_max - bbox.y_max,
+        ]
+        .into_iter()
+        .fold(f32::INFINITY, f32::min);
+        if edge_dist < border_margin * (1.0 - CLEARANCE_VERIFICATION_TOLERANCE) {
+            warn!("[CLR] item {} is {:.3} from the edge of the container (margin: {:.3})", pi.item_id, edge_dist, border_margin);
+            violations.push(ExtClearanceViolation {
+                item_ids: vec![item_features.original(pi.item_id) as u64],
+                distance: edge_dist,
+                clearance: border_margin,
+            });
+        }
+    }
+
+    let holes = &item_features.holes;
+    for ((pi1, s1, c1), (pi2, s2, c2)) in placed.iter().tuple_combinations() {
+        let required = (c1 + c2) / 2.0;
+        if required == 0.0 || !bboxes_within(s1, s2, required) {
+            continue;
+        }
+        //items nested in a hole are separated from the hole's boundary by the collision detection itself
+        let nested = shapes_are_nested(
+            &pi1.shape,
+            holes_of(holes, pi1.item_id),
+            pi1.d_transf,
+            &pi2.shape,
+            holes_of(holes, pi2.item_id),
+            pi2.d_transf,
+        );
+        if nested {
+            continue;
+        }
+        let dist = poly_distance(s1, s2);
+        if dist < required * (1.0 - CLEARANCE_VERIFICATION_TOLERANCE) {
+            warn!("[CLR] items {} and {} are {:.3} apart (clearance: {:.3})", pi1.item_id, pi2.item_id, dist, required);
+            violations.push(ExtClearanceViolation {
+                item_ids: vec![item_features.original(pi1.item_id) as u64, item_features.original(pi2.item_id) as u64],
+                distance: dist,
+                clearance: required,
+            });
+        }
+    }
+
+    if violations.is_empty() {
+        info!("[CLR] all clearances are respected");
+    }
+    violations
+}
+
+/// Whether the bounding boxes of both shapes are within `dist` of each other
+fn bboxes_within(s1: &SPolygon, s2: &SPolygon, dist: f32) -> bool {
+    let (b1, b2) = (s1.bbox, s2.bbox);
+    b1.x_min - dist <= b2.x_max && b2.x_min - dist <= b1.x_max && b1.y_min - dist <= b2.y_max && b2.y_min - dist <= b1.y_max
+}
+
+/// Minimum distance between two polygons, zero if they overlap
+fn poly_distance(s1: &SPolygon, s2: &SPolygon) -> f32 {
+    let vertex_dist = |p: &SPolygon, q: &SPolygon| {
+        p.vertices
+            .iter()
+            .map(|v| match q.collides_with(v) {
+                true => 0.0,
+                false => (0..q.n_vertices())
+                    .map(|i| q.edge(i).distance_to(v))
+                    .fold(f32::INFINITY, f32::min),
+            })
+            .fold(f32::INFINITY, f32::min)
+    };
+    f32::min(vertex_dist(s1, s2), vertex_dist(s2, s1))
+}
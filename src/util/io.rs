@@ -1,4 +1,4 @@
-use log::{log, Level, LevelFilter};
+use log::{info, log, warn, Level, LevelFilter};
 use std::fs;
 use std::fs::File;
 use std::io::BufReader;
@@ -6,9 +6,35 @@ use std::path::Path;
 use serde::{Deserialize, Serialize};
 use svg::Document;
 use anyhow::{Context, Result};
-use clap::Parser;
-use jagua_rs::probs::spp::io::ext_repr::{ExtSPInstance, ExtSPSolution};
+use clap::{Parser, ValueEnum};
+use jagua_rs::probs::bpp::io::ext_repr::{ExtBPInstance, ExtBPSolution};
+use jagua_rs::probs::spp::io::ext_repr::{ExtItem as ExtSPItem, ExtSPInstance, ExtSPSolution};
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use jagua_rs::entities::{Instance, Item};
+use ordered_float::OrderedFloat;
+use itertools::Itertools;
+use std::sync::Arc;
 use crate::EPOCH;
+use crate::config::{OpenDimObjective, SparrowConfig, WidthScheduleKind};
+use crate::optimizer::disrupt::Disruption;
+use crate::optimizer::lbf::InitSolution;
+use crate::optimizer::problem::{ItemFeatures, KitPart, StripFeatures};
+use crate::quantify::orientation::solution_orientation_cost;
+use crate::quantify::proximity::ProximityGroups;
+use crate::util::clearance::verify_clearances;
+use crate::util::proximity::verify_proximity;
+use crate::sample::rot_intervals::RotIntervals;
+use anyhow::bail;
+use jagua_rs::geometry::DTransformation;
+use jagua_rs::geometry::convex_hull::convex_hull_from_points;
+use jagua_rs::geometry::geo_traits::Transformable;
+use jagua_rs::geometry::original_shape::OriginalShape;
+use jagua_rs::geometry::primitives::{Point, Rect, SPolygon};
+use jagua_rs::geometry::shape_modification::ShapeModifyMode;
+use jagua_rs::entities::InferiorQualityZone;
+use jagua_rs::io::ext_repr::{ExtPolygon, ExtQualityZone, ExtRectangle, ExtShape, ExtSimplePolygon, ExtTransformation};
+use jagua_rs::io::import::{Importer, ext_to_int_transformation};
+use jagua_rs::probs::spp::entities::SPPlacement;
 
 #[derive(Parser)]
 pub struct MainCli {
@@ -34,13 +60,364 @@ pub struct MainCli {
 
     #[arg(short = 's', long, help = "Fixed seed for the random number generator")]
     pub rng_seed: Option<u64>,
+
+    /// Type of problem described by the input file
+    #[arg(short = 'm', long, value_enum, default_value_t = Mode::Strip, help = "Type of problem to solve")]
+    pub mode: Mode,
+
+    /// Objective of the open dimension mode
+    #[arg(long, value_enum, default_value_t = OpenDimObjective::Area, help = "Objective to minimize in open dimension mode")]
+    pub objective: OpenDimObjective,
+
+    /// Solution of a previous run to continue improving (strip packing only)
+    #[arg(long, help = "Path to a previous solution (JSON output) to warm start from")]
+    pub init_solution: Option<String>,
+
+    /// Disruption operators used in the exploration phase
+    #[arg(long, value_enum, value_delimiter = ',', help = "Disruption operators to use in the exploration phase (comma separated)")]
+    pub disruptions: Vec<Disruption>,
+
+    /// Schedule of the strip widths attempted in the exploration phase
+    #[arg(long, value_enum, default_value_t = WidthScheduleKind::Fixed, help = "Schedule of the strip widths attempted in the exploration phase")]
+    pub width_schedule: WidthScheduleKind,
+
+    /// Number of separators exploring in parallel (island model)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(2..), help = "Explore with multiple separators in parallel which exchange their best solutions")]
+    pub islands: Option<u64>,
+
+    /// Number of independent runs, only the best solution is written out (strip packing only)
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..), help = "Number of independent runs with different seeds, run in parallel")]
+    pub runs: u64,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Strip packing: minimize the width of a strip with fixed height
+    Strip,
+    /// Bin packing: minimize the cost of the sheets required to place all items
+    BinPacking,
+    /// Knapsack: maximize the total value of the items placed in a strip with fixed width and height
+    Knapsack,
+    /// Open dimension: minimize the area (or perimeter) of a container with variable width and height
+    OpenDimension,
+    /// Roll selection: select the cheapest strip height from a catalogue of rolls
+    RollSelection,
+    /// Folded strip packing: fabric spread face-to-face, every item placed on one half of the strip is mirrored onto the other
+    FoldedStrip,
+    /// Marker planning: choose the size ratios of the markers for an order of garments and nest every marker.
+    /// The ratios follow a fixed greedy plan (fewest markers, most plies), they are not compared by fabric length
+    MarkerPlanning,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct SPOutput {
     #[serde(flatten)]
     pub instance: ExtSPInstance,
+    #[serde(flatten)]
+    pub strip_features: ExtStripFeatures,
+    pub solution: ExtSPSolution,
+    /// Final size of the container, only present if its height was variable as well
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_size: Option<ExtContainerSize>,
+    /// Indices of the placed items in the solution which are mirrored (x -> -x) before their transformation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrored_items: Vec<usize>,
+    /// Optional copies of items placed on top of their demand
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_items: Vec<ExtItemQuantity>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clearance_violations: Vec<ExtClearanceViolation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub proximity_violations: Vec<ExtProximityViolation>,
+    /// Total cost of the disfavored orientations in the solution, only present if the items have orientation costs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orientation_cost: Option<f32>,
+    /// Summary of all runs, only present if multiple runs were launched
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub runs: Vec<ExtRunSummary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct ExtRunSummary {
+    pub run: usize,
+    /// Seed of the random number generator of the run
+    pub seed: u64,
+    pub strip_width: f32,
+    pub density: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtItemQuantity {
+    pub item_id: u64,
+    pub quantity: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct ExtContainerSize {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Optional properties of the strip, stored alongside the strip packing instance in the same JSON file
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ExtStripFeatures {
+    /// Defects (holes) at fixed positions in the strip, in absolute coordinates
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub defects: Vec<ExtShape>,
+    /// Zones of inferior quality in the strip, in absolute coordinates
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub quality_zones: Vec<ExtQualityZone>,
+    /// Margin along all edges of the strip which cannot be used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border_margin: Option<f32>,
+    /// Items which are already placed at a fixed position in the strip
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned_items: Vec<ExtPinnedItem>,
+}
+
+/// Placement of an item which is never moved, in the same format as the placed items of a solution
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtPinnedItem {
+    pub item_id: u64,
+    pub transformation: ExtTransformation,
+    /// Whether the item is mirrored (x -> -x) before its transformation, the item should allow mirroring
+    #[serde(default)]
+    pub mirrored: bool,
+}
+
+/// Optional properties of the items, stored alongside the items (matched by id) in the same JSON file
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ExtItemFeatures {
+    #[serde(default)]
+    pub items: Vec<ExtItemAttributes>,
+    /// Groups of items which are placed with fixed relative positions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kits: Vec<ExtKit>,
+    /// Groups of items which should be placed close to each other, e.g. pieces of the same garment
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub proximity_groups: Vec<ExtProximityGroup>,
+}
+
+/// Soft constraint: the x-distance between any two items of the group should not exceed the maximum distance.
+/// It only ranks the collision-free positions of the items during separation, violations are reported in the output.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtProximityGroup {
+    pub item_ids: Vec<u64>,
+    pub max_distance: f32,
+}
+
+/// Item(s) closer to each other (or to the edge of the strip) than their required clearance.
+/// A violation of the border margin refers to a single item.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtClearanceViolation {
+    pub item_ids: Vec<u64>,
+    pub distance: f32,
+    pub clearance: f32,
+}
+
+/// Proximity group of which some items are further apart than allowed, along with the largest x-distance between them
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtProximityViolation {
+    pub group: usize,
+    pub distance: f32,
+    pub max_distance: f32,
+}
+
+/// A group of items which is placed as a single rigid unit.
+/// Kits are numbered after the items of the instance: the first kit has id `#items`, the second `#items + 1`, ...
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtKit {
+    pub demand: u64,
+    /// Allowed orientations (in degrees) of the kit as a whole, continuous if none are given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_orientations: Option<Vec<f32>>,
+    pub parts: Vec<ExtKitPart>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtKitPart {
+    pub item_id: u64,
+    /// Rotation (in degrees) of the item within the kit
+    #[serde(default)]
+    pub rotation: f32,
+    /// Translation of the item within the kit
+    #[serde(default)]
+    pub translation: (f32, f32),
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ExtItemAttributes {
+    pub id: u64,
+    /// Whether the item is allowed to be mirrored
+    #[serde(default)]
+    pub allow_mirror: bool,
+    /// Minimum clearance (e.g. kerf) to other items, defaults to the global minimum item separation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clearance: Option<f32>,
+    /// Intervals (in degrees) to which the rotation of the item is restricted, overrides the allowed orientations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation_intervals: Option<Vec<(f32, f32)>>,
+    /// Maximum number of copies of the item, the demand is the minimum.
+    /// Copies on top of the demand are only placed in the free space of the final strip.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_demand: Option<u64>,
+    /// Region of the strip the item has to lie within (e.g. near the selvedge or in the first meters of the roll)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<ExtRegion>,
+    /// Costs of disfavored orientations as (rotation in degrees, cost) pairs, e.g. `[[180.0, 1.0]]` if 0° is preferred.
+    /// Orientations without a cost are free.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orientation_costs: Option<Vec<(f32, f32)>>,
+}
+
+/// Region of the strip bounded by an x and/or y interval, unbounded along an axis without interval
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct ExtRegion {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<(f32, f32)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<(f32, f32)>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BPOutput {
+    #[serde(flatten)]
+    pub instance: ExtBPInstance,
+    pub solution: ExtBPSolution,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clearance_violations: Vec<ExtClearanceViolation>,
+}
+
+/// Catalogue of rolls which can be used as strip, stored alongside the strip packing instance in the same JSON file
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtRollCatalogue {
+    pub rolls: Vec<ExtRoll>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct ExtRoll {
+    /// Width of the roll, which is the height of the strip
+    pub height: f32,
+    /// Cost per unit of length of the roll
+    pub cost: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RSOutput {
+    #[serde(flatten)]
+    pub output: SPOutput,
+    pub roll_summary: Vec<ExtRollSummary>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtRollSummary {
+    #[serde(flatten)]
+    pub roll: ExtRoll,
+    pub selected: bool,
+    pub strip_width: Option<f32>,
+    pub total_cost: Option<f32>,
+    pub dropped_in_round: Option<usize>,
+}
+
+/// Order of garments for marker planning
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtMarkerOrder {
+    pub sizes: Vec<ExtSize>,
+    /// Maximum number of plies a marker can be cut from
+    pub max_plies: usize,
+    /// Maximum number of garments nested together in a single marker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_garments_per_marker: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtSize {
+    pub name: String,
+    /// Items (or kits) making up a single garment of this size, their demand is the quantity per garment
+    pub item_ids: Vec<u64>,
+    /// Number of garments to cut in this size
+    pub order_qty: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MPOutput {
+    pub name: String,
+    pub strip_height: f32,
+    pub markers: Vec<ExtMarker>,
+    /// Total length of fabric required to cut all markers on their plies
+    pub total_length: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtMarker {
+    /// Number of garments per size in the marker
+    pub ratios: Vec<ExtSizeRatio>,
+    pub plies: usize,
+    /// Length (strip width) of the marker
+    pub length: f32,
+    pub solution: ExtSPSolution,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrored_items: Vec<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clearance_violations: Vec<ExtClearanceViolation>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtSizeRatio {
+    pub size: String,
+    pub ratio: usize,
+}
+
+/// Knapsack instance: a strip packing instance with a fixed strip width and a value for every item
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtKPInstance {
+    pub name: String,
+    pub items: Vec<ExtKPItem>,
+    pub strip_height: f32,
+    pub strip_width: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtKPItem {
+    #[serde(flatten)]
+    pub base: ExtSPItem,
+    /// Value of a single copy of the item, defaults to its area
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<f32>,
+}
+
+impl ExtKPInstance {
+    /// Strip packing instance containing the same items, values are dropped
+    pub fn to_spp(&self) -> ExtSPInstance {
+        ExtSPInstance {
+            name: self.name.clone(),
+            items: self.items.iter().map(|i| i.base.clone()).collect(),
+            strip_height: self.strip_height,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct KPOutput {
+    #[serde(flatten)]
+    pub instance: ExtKPInstance,
+    #[serde(flatten)]
+    pub strip_features: ExtStripFeatures,
     pub solution: ExtSPSolution,
+    pub total_value: f32,
+    /// Items (and their quantity) which could not be placed in the container
+    pub unplaced_items: Vec<ExtUnplacedItem>,
+    /// Indices of the placed items in the solution which are mirrored (x -> -x) before their transformation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrored_items: Vec<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clearance_violations: Vec<ExtClearanceViolation>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtUnplacedItem {
+    pub item_id: u64,
+    pub quantity: usize,
 }
 
 pub fn init_logger(level_filter: LevelFilter, log_file_path: &Path) -> Result<()> {
@@ -116,3 +493,803 @@ pub fn read_spp_instance_json(path: &Path) -> Result<ExtSPInstance> {
     serde_json::from_reader(BufReader::new(file))
         .context("not a valid strip packing instance (ExtSPInstance)")
 }
+
+pub fn read_strip_features_json(path: &Path) -> Result<ExtStripFeatures> {
+    let file = File::open(path).context("could not open instance file")?;
+    serde_json::from_reader(BufReader::new(file))
+        .context("not a valid set of strip features (ExtStripFeatures)")
+}
+
+pub fn import_strip_features(importer: &Importer, ext: &ExtStripFeatures, strip_height: f32) -> Result<StripFeatures> {
+    let border_margin = ext.border_margin.unwrap_or(0.0);
+    if border_margin < 0.0 || 2.0 * border_margin >= strip_height {
+        bail!("border margin {} does not fit within the strip (height: {})", border_margin, strip_height);
+    }
+
+    let defects = ext
+        .defects
+        .iter()
+        .map(|ext_shape| import_fixed_shape(importer, ext_shape))
+        .collect::<Result<Vec<_>>>()?;
+
+    let quality_zones = ext
+        .quality_zones
+        .iter()
+        .map(|ext_zone| {
+            let shapes = ext_zone
+                .shapes
+                .iter()
+                .map(|ext_shape| import_fixed_shape(importer, ext_shape))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(InferiorQualityZone::new(ext_zone.quality, shapes))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(StripFeatures {
+        defects,
+        quality_zones,
+        border_margin,
+        pinned_items: vec![],
+    })
+}
+
+/// Imports the pinned items, which are placed as a mirrored twin if they are mirrored
+pub fn import_pinned_items(
+    instance: &SPInstance,
+    item_features: &ItemFeatures,
+    ext: &ExtStripFeatures,
+) -> Result<Vec<SPPlacement>> {
+    let mut n_pinned = vec![0; instance.items.len()];
+    ext.pinned_items
+        .iter()
+        .map(|ext_pinned| {
+            let placement = import_placement(
+                instance,
+                item_features,
+                ext_pinned.item_id,
+                &ext_pinned.transformation,
+                ext_pinned.mirrored,
+            )?;
+            let original_id = item_features.original(placement.item_id);
+            n_pinned[original_id] += 1;
+            if n_pinned[original_id] > instance.items[original_id].1 {
+                bail!("more copies of item {} are pinned than demanded", original_id);
+            }
+            Ok(placement)
+        })
+        .collect()
+}
+
+/// Converts the placement of an (original) item in the external format to a placement in the instance,
+/// as its mirrored twin if the item is mirrored.
+fn import_placement(
+    instance: &SPInstance,
+    item_features: &ItemFeatures,
+    ext_item_id: u64,
+    ext_transf: &ExtTransformation,
+    mirrored: bool,
+) -> Result<SPPlacement> {
+    let item_id = ext_item_id as usize;
+    if item_id >= instance.items.len() || item_features.is_mirrored(item_id) {
+        bail!("placement refers to unknown item {}", item_id);
+    }
+    let item_id = match mirrored {
+        true => match item_features.mirror_twin(item_id) {
+            Some(twin_id) => twin_id,
+            None => bail!("item {} is placed mirrored, but does not allow mirroring", item_id),
+        },
+        false => item_id,
+    };
+    let item = instance.item(item_id);
+    let d_transf = ext_to_int_transformation(ext_transf, &item.shape_orig.pre_transform);
+    Ok(SPPlacement { item_id, d_transf })
+}
+
+pub fn read_spp_output_json(path: &Path) -> Result<SPOutput> {
+    let file = File::open(path).context("could not open solution file")?;
+    serde_json::from_reader(BufReader::new(file))
+        .context("not a valid strip packing solution (SPOutput)")
+}
+
+/// Imports the solution of a previous run (as written by `main.rs`) to warm start the optimization from
+pub fn import_init_solution(
+    instance: &SPInstance,
+    item_features: &ItemFeatures,
+    output: &SPOutput,
+) -> Result<InitSolution> {
+    if item_features.kits.iter().any(|parts| !parts.is_empty()) {
+        //kits are exported as their individual parts, which cannot be reliably regrouped
+        bail!("warm starting instances with kits is not supported");
+    }
+    let placements = output
+        .solution
+        .layout
+        .placed_items
+        .iter()
+        .enumerate()
+        .map(|(i, ext_pi)| {
+            let mirrored = output.mirrored_items.contains(&i);
+            import_placement(instance, item_features, ext_pi.item_id, &ext_pi.transformation, mirrored)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(InitSolution {
+        strip_width: output.solution.strip_width,
+        placements,
+    })
+}
+
+/// Imports a shape at a fixed position in the strip (defect or quality zone)
+fn import_fixed_shape(importer: &Importer, ext_shape: &ExtShape) -> Result<OriginalShape> {
+    let shape = match ext_shape {
+        ExtShape::Rectangle(r) => SPolygon::from(Rect::try_new(
+            r.x_min,
+            r.y_min,
+            r.x_min + r.width,
+            r.y_min + r.height,
+        )?),
+        ExtShape::SimplePolygon(sp) => SPolygon::new(sp.0.iter().map(|&(x, y)| Point(x, y)).collect())?,
+        ExtShape::Polygon(p) if p.inner.is_empty() => {
+            SPolygon::new(p.outer.0.iter().map(|&(x, y)| Point(x, y)).collect())?
+        }
+        _ => bail!("shapes at fixed positions in the strip should be simple polygons"),
+    };
+    //inflate the shape to respect the minimum separation between items and the shape
+    Ok(OriginalShape {
+        shape,
+        pre_transform: DTransformation::empty(),
+        modify_mode: ShapeModifyMode::Inflate,
+        modify_config: importer.shape_modify_config,
+    })
+}
+
+pub fn read_item_features_json(path: &Path) -> Result<ExtItemFeatures> {
+    let file = File::open(path).context("could not open instance file")?;
+    serde_json::from_reader(BufReader::new(file))
+        .context("not a valid set of item features (ExtItemFeatures)")
+}
+
+/// Adds an item to the instance for every kit, appended after the original items.
+/// jagua-rs items consist of a single simple polygon, so the shape of a kit is the convex hull of its parts.
+/// Returns the parts of every item in the extended instance (empty for regular items).
+pub fn add_kits(ext_instance: &ExtSPInstance, ext_features: &ExtItemFeatures) -> Result<(ExtSPInstance, Vec<Vec<KitPart>>)> {
+    let mut kitted_instance = ext_instance.clone();
+    let mut kits = vec![vec![]; ext_instance.items.len()];
+
+    for ext_kit in ext_features.kits.iter() {
+        let Some(first_part) = ext_kit.parts.first() else {
+            bail!("kit without parts");
+        };
+        let mut parts = vec![];
+        let mut hull_points = vec![];
+        for ext_part in ext_kit.parts.iter() {
+            let Some(ext_item) = ext_instance.items.get(ext_part.item_id as usize) else {
+                bail!("kit part refers to unknown item {}", ext_part.item_id);
+            };
+            let offset = DTransformation::new(ext_part.rotation.to_radians(), ext_part.translation);
+            let t = offset.compose();
+            hull_points.extend(ext_shape_outer_points(&ext_item.base.shape).into_iter().map(|p| p.transform_clone(&t)));
+            parts.push(KitPart {
+                item_id: ext_part.item_id as usize,
+                offset,
+            });
+        }
+        let hull = convex_hull_from_points(hull_points);
+
+        let kit_id = kitted_instance.items.len();
+        let mut kit_item = ext_instance.items[first_part.item_id as usize].clone();
+        kit_item.base.id = kit_id as u64;
+        kit_item.base.shape = ExtShape::SimplePolygon(ExtSimplePolygon(hull.into_iter().map(|Point(x, y)| (x, y)).collect()));
+        kit_item.base.allowed_orientations = ext_kit.allowed_orientations.clone();
+        kit_item.demand = ext_kit.demand;
+        kitted_instance.items.push(kit_item);
+        kits.push(parts);
+    }
+
+    Ok((kitted_instance, kits))
+}
+
+/// All vertices of the outer boundary of a shape
+fn ext_shape_outer_points(ext_shape: &ExtShape) -> Vec<Point> {
+    match ext_shape {
+        ExtShape::Rectangle(r) => vec![
+            Point(r.x_min, r.y_min),
+            Point(r.x_min + r.width, r.y_min),
+            Point(r.x_min + r.width, r.y_min + r.height),
+            Point(r.x_min, r.y_min + r.height),
+        ],
+        ExtShape::SimplePolygon(sp) => sp.0.iter().map(|&(x, y)| Point(x, y)).collect(),
+        ExtShape::Polygon(p) => p.outer.0.iter().map(|&(x, y)| Point(x, y)).collect(),
+        ExtShape::MultiPolygon(mp) => mp.iter().flat_map(|p| p.outer.0.iter()).map(|&(x, y)| Point(x, y)).collect(),
+    }
+}
+
+/// Folds a strip packing instance for fabric spread face-to-face: the strip height is halved and the demand of every item
+/// (and kit) as well, rounded up, since every item placed on the folded strip yields two copies.
+/// Every item is allowed to be mirrored: the copy on the other half is mirrored either way.
+pub fn fold_instance(ext_instance: &ExtSPInstance, ext_features: &ExtItemFeatures) -> (ExtSPInstance, ExtItemFeatures) {
+    let mut folded_instance = ext_instance.clone();
+    folded_instance.strip_height /= 2.0;
+    for ext_item in folded_instance.items.iter_mut() {
+        if ext_item.demand % 2 == 1 {
+            warn!("[FOLD] odd demand of item {}, one extra copy will be placed", ext_item.base.id);
+        }
+        ext_item.demand = ext_item.demand.div_ceil(2);
+    }
+
+    let mut folded_features = ext_features.clone();
+    for kit in folded_features.kits.iter_mut() {
+        kit.demand = kit.demand.div_ceil(2);
+    }
+    let n_items = ext_instance.items.len() as u64;
+    let kit_ids = n_items..n_items + ext_features.kits.len() as u64;
+    for id in ext_instance.items.iter().map(|item| item.base.id).chain(kit_ids) {
+        if !folded_features.items.iter().any(|attr| attr.id == id) {
+            folded_features.items.push(ExtItemAttributes { id, ..ExtItemAttributes::default() });
+        }
+    }
+    for attr in folded_features.items.iter_mut() {
+        attr.allow_mirror = true;
+        attr.max_demand = attr.max_demand.map(|max| max.div_ceil(2));
+    }
+    (folded_instance, folded_features)
+}
+
+/// Instance of a single marker: the demand of every item (and kit) is its quantity per garment multiplied by the
+/// ratio of the size it belongs to. Items which do not belong to any size are not placed.
+/// Extra copies (`max_demand`) are dropped, a marker contains exactly its garments.
+pub fn marker_instance(
+    ext_instance: &ExtSPInstance,
+    ext_features: &ExtItemFeatures,
+    sizes: &[ExtSize],
+    ratios: &[usize],
+) -> Result<(ExtSPInstance, ExtItemFeatures)> {
+    let n_items = ext_instance.items.len() as u64;
+    let mut size_of = vec![None; ext_instance.items.len() + ext_features.kits.len()];
+    for (s, size) in sizes.iter().enumerate() {
+        for id in size.item_ids.iter() {
+            let Some(entry) = size_of.get_mut(*id as usize) else {
+                bail!("size {} refers to unknown item {id}", size.name);
+            };
+            if entry.replace(s).is_some() {
+                bail!("item {id} belongs to multiple sizes");
+            }
+        }
+    }
+    let marker_demand = |id: u64, demand: u64| size_of[id as usize].map_or(0, |s: usize| demand * ratios[s] as u64);
+
+    let mut marker_instance = ext_instance.clone();
+    for (id, ext_item) in marker_instance.items.iter_mut().enumerate() {
+        ext_item.demand = marker_demand(id as u64, ext_item.demand);
+    }
+    let mut marker_features = ext_features.clone();
+    for (k, kit) in marker_features.kits.iter_mut().enumerate() {
+        kit.demand = marker_demand(n_items + k as u64, kit.demand);
+    }
+    for attr in marker_features.items.iter_mut() {
+        attr.max_demand = None;
+    }
+    Ok((marker_instance, marker_features))
+}
+
+/// Adds a mirrored twin to the instance for every item which is allowed to be mirrored.
+/// Twins are appended after the original items, with the same demand and allowed orientations.
+pub fn add_mirrored_twins(ext_instance: &ExtSPInstance, ext_features: &ExtItemFeatures) -> (ExtSPInstance, ItemFeatures) {
+    let mut twinned_instance = ext_instance.clone();
+    let mut mirror_twins = vec![None; ext_instance.items.len()];
+
+    for (id, ext_item) in ext_instance.items.iter().enumerate() {
+        let allow_mirror = ext_features
+            .items
+            .iter()
+            .any(|attr| attr.id == ext_item.base.id && attr.allow_mirror);
+        if allow_mirror {
+            let twin_id = twinned_instance.items.len();
+            let mut twin = ext_item.clone();
+            twin.base.id = twin_id as u64;
+            twin.base.shape = mirror_ext_shape(&ext_item.base.shape);
+            twinned_instance.items.push(twin);
+            mirror_twins[id] = Some(twin_id);
+            mirror_twins.push(Some(id));
+        }
+    }
+
+    (
+        twinned_instance,
+        ItemFeatures {
+            mirror_twins,
+            ..ItemFeatures::default()
+        },
+    )
+}
+
+/// Number of optional copies of every (original) item on top of its demand, empty if there are none
+pub fn item_extra_qtys(
+    ext_instance: &ExtSPInstance,
+    ext_features: &ExtItemFeatures,
+    item_features: &ItemFeatures,
+) -> Result<Vec<usize>> {
+    let extra_qtys = ext_instance
+        .items
+        .iter()
+        .enumerate()
+        .map(|(id, ext_item)| {
+            let max_demand = ext_features
+                .items
+                .iter()
+                .find(|attr| attr.id == ext_item.base.id)
+                .and_then(|attr| attr.max_demand);
+            match max_demand {
+                _ if item_features.is_mirrored(id) => Ok(0),
+                Some(max) if max < ext_item.demand => {
+                    bail!("maximum demand of item {} is lower than its demand", ext_item.base.id)
+                }
+                Some(max) => Ok((max - ext_item.demand) as usize),
+                None => Ok(0),
+            }
+        })
+        .collect::<Result<Vec<usize>>>()?;
+
+    match extra_qtys.iter().all(|qty| *qty == 0) {
+        true => Ok(vec![]),
+        false => Ok(extra_qtys),
+    }
+}
+
+/// Optional copies of the (original) items placed in the solution on top of their demand
+pub fn extra_items(instance: &SPInstance, solution: &SPSolution, item_features: &ItemFeatures) -> Vec<ExtItemQuantity> {
+    let placed_qtys = solution
+        .layout_snapshot
+        .placed_items
+        .values()
+        .map(|pi| item_features.original(pi.item_id))
+        .counts();
+    placed_qtys
+        .into_iter()
+        .filter(|(id, qty)| *qty > instance.items[*id].1)
+        .sorted()
+        .map(|(id, qty)| ExtItemQuantity {
+            item_id: id as u64,
+            quantity: qty - instance.items[id].1,
+        })
+        .collect()
+}
+
+/// Clearance of every item (mirrored twins included), defaults to the global minimum item separation
+pub fn item_clearances(
+    ext_instance: &ExtSPInstance,
+    ext_features: &ExtItemFeatures,
+    item_features: &ItemFeatures,
+    default_clearance: Option<f32>,
+) -> Vec<f32> {
+    (0..ext_instance.items.len())
+        .map(|id| {
+            let original_id = ext_instance.items[item_features.original(id)].base.id;
+            ext_features
+                .items
+                .iter()
+                .find(|attr| attr.id == original_id)
+                .and_then(|attr| attr.clearance)
+                .or(default_clearance)
+                .unwrap_or(0.0)
+        })
+        .collect()
+}
+
+/// Rotation intervals of every item (mirrored twins included, with the same intervals as their original).
+/// Items with rotation intervals are made continuously rotatable in the instance, the intervals are enforced by the samplers.
+pub fn item_rotation_intervals(
+    ext_instance: &mut ExtSPInstance,
+    ext_features: &ExtItemFeatures,
+    item_features: &ItemFeatures,
+) -> Vec<Option<RotIntervals>> {
+    let rot_intervals = (0..ext_instance.items.len())
+        .map(|id| {
+            let original_id = ext_instance.items[item_features.original(id)].base.id;
+            ext_features
+                .items
+                .iter()
+                .find(|attr| attr.id == original_id)
+                .and_then(|attr| attr.rotation_intervals.as_ref())
+                .filter(|intervals| !intervals.is_empty())
+                .map(|intervals| {
+                    let intervals = intervals
+                        .iter()
+                        .map(|&(s, e)| (s.to_radians(), e.to_radians()))
+                        .collect_vec();
+                    RotIntervals::new(intervals)
+                })
+        })
+        .collect_vec();
+
+    for (ext_item, ri) in ext_instance.items.iter_mut().zip(rot_intervals.iter()) {
+        if ri.is_some() {
+            ext_item.base.allowed_orientations = None;
+        }
+    }
+    rot_intervals
+}
+
+/// Placement region of every item (mirrored twins included, with the same region as their original),
+/// empty if no item has a region
+pub fn item_regions(
+    ext_instance: &ExtSPInstance,
+    ext_features: &ExtItemFeatures,
+    item_features: &ItemFeatures,
+) -> Result<Arc<[Option<Rect>]>> {
+    let regions = (0..ext_instance.items.len())
+        .map(|id| {
+            let original_id = ext_instance.items[item_features.original(id)].base.id;
+            let region = ext_features
+                .items
+                .iter()
+                .find(|attr| attr.id == original_id)
+                .and_then(|attr| attr.region);
+            match region {
+                Some(ExtRegion { x, y }) => {
+                    let (x_min, x_max) = x.unwrap_or((f32::NEG_INFINITY, f32::INFINITY));
+                    let (y_min, y_max) = y.unwrap_or((f32::NEG_INFINITY, f32::INFINITY));
+                    Rect::try_new(x_min, y_min, x_max, y_max)
+                        .map(Some)
+                        .with_context(|| format!("invalid region for item {original_id}"))
+                }
+                None => Ok(None),
+            }
+        })
+        .collect::<Result<Vec<Option<Rect>>>>()?;
+
+    match regions.iter().all(|r| r.is_none()) {
+        true => Ok(Arc::default()),
+        false => Ok(regions.into()),
+    }
+}
+
+/// Orientation costs of every item (in radians), empty if no item has any.
+/// Mirrored twins point along the opposite direction of their original, so their costs are turned by 180°.
+pub fn item_orientation_costs(
+    ext_instance: &ExtSPInstance,
+    ext_features: &ExtItemFeatures,
+    item_features: &ItemFeatures,
+) -> Vec<Vec<(f32, f32)>> {
+    let costs = (0..ext_instance.items.len())
+        .map(|id| {
+            let original_id = ext_instance.items[item_features.original(id)].base.id;
+            let turn = match item_features.is_mirrored(id) {
+                true => 180.0,
+                false => 0.0,
+            };
+            ext_features
+                .items
+                .iter()
+                .find(|attr| attr.id == original_id)
+                .and_then(|attr| attr.orientation_costs.as_ref())
+                .map(|costs| costs.iter().map(|&(o, c)| ((o + turn).to_radians(), c)).collect_vec())
+                .unwrap_or_default()
+        })
+        .collect_vec();
+
+    match costs.iter().all(|c| c.is_empty()) {
+        true => vec![],
+        false => costs,
+    }
+}
+
+/// Proximity groups of the items (mirrored twins belong to the group of their original)
+pub fn item_proximity_groups(
+    ext_instance: &ExtSPInstance,
+    ext_features: &ExtItemFeatures,
+    item_features: &ItemFeatures,
+) -> Result<Arc<ProximityGroups>> {
+    let mut group_of = vec![None; ext_instance.items.len()];
+    for (group, ext_group) in ext_features.proximity_groups.iter().enumerate() {
+        if ext_group.max_distance < 0.0 {
+            bail!("maximum distance of proximity group {group} should be non-negative");
+        }
+        for ext_id in ext_group.item_ids.iter() {
+            let Some(id) = ext_instance.items.iter().position(|item| item.base.id == *ext_id) else {
+                bail!("proximity group {group} refers to unknown item {ext_id}");
+            };
+            if group_of[id].is_some() {
+                bail!("item {ext_id} belongs to multiple proximity groups");
+            }
+            group_of[id] = Some(group);
+        }
+    }
+    for id in 0..group_of.len() {
+        group_of[id] = group_of[item_features.original(id)];
+    }
+    Ok(Arc::new(ProximityGroups {
+        group_of,
+        max_dists: ext_features.proximity_groups.iter().map(|g| g.max_distance).collect(),
+    }))
+}
+
+/// Imports a strip packing instance along with the features of its items and of its strip, including the pinned items
+pub fn import_spp_problem(
+    config: &SparrowConfig,
+    ext_instance: &ExtSPInstance,
+    ext_item_features: &ExtItemFeatures,
+    ext_strip_features: &ExtStripFeatures,
+) -> Result<(SPInstance, ItemFeatures, StripFeatures)> {
+    let importer = Importer::new(
+        config.cde_config,
+        config.poly_simpl_tolerance,
+        config.min_item_separation,
+        config.narrow_concavity_cutoff_ratio,
+    );
+    let (instance, item_features) = import_featured_spp_instance(config, ext_instance, ext_item_features)?;
+    let mut strip_features = import_strip_features(&importer, ext_strip_features, instance.strip_height)?;
+    strip_features.pinned_items = import_pinned_items(&instance, &item_features, ext_strip_features)?;
+    Ok((instance, item_features, strip_features))
+}
+
+/// Imports a strip packing instance along with the features of its items (kits, mirroring, holes, clearances, rotation intervals, regions, proximity groups and orientation costs)
+pub fn import_featured_spp_instance(
+    config: &SparrowConfig,
+    ext_instance: &ExtSPInstance,
+    ext_item_features: &ExtItemFeatures,
+) -> Result<(SPInstance, ItemFeatures)> {
+    let (kitted_instance, kits) = add_kits(ext_instance, ext_item_features)?;
+    let (mut twinned_instance, mut item_features) = add_mirrored_twins(&kitted_instance, ext_item_features);
+    //mirrored twins of kits consist of the same parts, mirrored on export
+    item_features.kits = (0..twinned_instance.items.len())
+        .map(|id| kits[item_features.original(id)].clone())
+        .collect();
+    item_features.rot_intervals = item_rotation_intervals(&mut twinned_instance, ext_item_features, &item_features);
+    item_features.extra_qtys = item_extra_qtys(&twinned_instance, ext_item_features, &item_features)?;
+    item_features.regions = item_regions(&twinned_instance, ext_item_features, &item_features)?;
+    item_features.proximity = item_proximity_groups(&twinned_instance, ext_item_features, &item_features)?;
+    item_features.orientation_costs = item_orientation_costs(&twinned_instance, ext_item_features, &item_features);
+    item_features.clearances = item_clearances(
+        &twinned_instance,
+        ext_item_features,
+        &item_features,
+        config.min_item_separation,
+    );
+    let instance = import_spp_instance(config, &twinned_instance, &item_features.clearances)?;
+    item_features.holes = import_item_holes(config, &twinned_instance, &instance, &item_features.clearances)?;
+    Ok((instance, item_features))
+}
+
+/// Imports a strip packing instance in which every item is inflated by half of its own clearance,
+/// instead of a single global separation. Items are imported in groups of equal clearance.
+pub fn import_spp_instance(config: &SparrowConfig, ext_instance: &ExtSPInstance, clearances: &[f32]) -> Result<SPInstance> {
+    let mut items: Vec<Option<(Item, usize)>> = ext_instance.items.iter().map(|_| None).collect();
+    for clearance in clearances.iter().copied().unique_by(|c| OrderedFloat(*c)) {
+        let importer = Importer::new(
+            config.cde_config,
+            config.poly_simpl_tolerance,
+            (clearance > 0.0).then_some(clearance),
+            config.narrow_concavity_cutoff_ratio,
+        );
+        let group_instance = jagua_rs::probs::spp::io::import(&importer, ext_instance)?;
+        for (id, (item, qty)) in group_instance.items.into_iter().enumerate() {
+            if clearances[id] == clearance {
+                items[id] = Some((item, qty));
+            }
+        }
+    }
+    let items = items.into_iter().map(|i| i.expect("every item should be imported")).collect();
+    Ok(SPInstance::new(items, ext_instance.strip_height))
+}
+
+/// Imports the holes in the shapes of the items, in which other items can be nested.
+/// The holes are expressed in the reference frame of the imported items and deflated by half of the item's clearance. Returns an empty list if no item has holes.
+pub fn import_item_holes(
+    config: &SparrowConfig,
+    ext_instance: &ExtSPInstance,
+    instance: &SPInstance,
+    clearances: &[f32],
+) -> Result<Arc<[Vec<SPolygon>]>> {
+    let ext_holes = ext_instance
+        .items
+        .iter()
+        .map(|ext_item| match &ext_item.base.shape {
+            ExtShape::Polygon(p) => p.inner.iter().collect_vec(),
+            ExtShape::MultiPolygon(mp) => mp.iter().flat_map(|p| p.inner.iter()).collect_vec(),
+            _ => vec![],
+        })
+        .collect_vec();
+
+    if ext_holes.iter().all(|h| h.is_empty()) {
+        return Ok(Arc::default());
+    }
+
+    ext_holes
+        .iter()
+        .enumerate()
+        .map(|(id, holes)| {
+            let pre_transform = instance.item(id).shape_orig.pre_transform;
+            let importer = Importer::new(
+                config.cde_config,
+                config.poly_simpl_tolerance,
+                (clearances[id] > 0.0).then_some(clearances[id]),
+                config.narrow_concavity_cutoff_ratio,
+            );
+            holes
+                .iter()
+                .map(|ext_hole| {
+                    let hole = OriginalShape {
+                        shape: SPolygon::new(ext_hole.0.iter().map(|&(x, y)| Point(x, y)).collect())?,
+                        pre_transform,
+                        modify_mode: ShapeModifyMode::Deflate,
+                        modify_config: importer.shape_modify_config,
+                    };
+                    hole.convert_to_internal()
+                })
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect()
+}
+
+/// Mirrors a shape around the y-axis (x -> -x), reversing the vertices to preserve their winding order
+fn mirror_ext_shape(ext_shape: &ExtShape) -> ExtShape {
+    let mirror_sp = |sp: &ExtSimplePolygon| ExtSimplePolygon(sp.0.iter().rev().map(|&(x, y)| (-x, y)).collect());
+    let mirror_p = |p: &ExtPolygon| ExtPolygon {
+        outer: mirror_sp(&p.outer),
+        inner: p.inner.iter().map(mirror_sp).collect(),
+    };
+    match ext_shape {
+        ExtShape::Rectangle(r) => ExtShape::Rectangle(ExtRectangle {
+            x_min: -(r.x_min + r.width),
+            y_min: r.y_min,
+            width: r.width,
+            height: r.height,
+        }),
+        ExtShape::SimplePolygon(sp) => ExtShape::SimplePolygon(mirror_sp(sp)),
+        ExtShape::Polygon(p) => ExtShape::Polygon(mirror_p(p)),
+        ExtShape::MultiPolygon(mp) => ExtShape::MultiPolygon(mp.iter().map(mirror_p).collect()),
+    }
+}
+
+/// Replaces the placements of kits in an exported solution by the placements of their parts.
+/// Parts of a mirrored kit are mirrored as well.
+/// Takes the indices of the mirrored placed items before and returns them after the replacement.
+pub fn export_kits(ext_solution: &mut ExtSPSolution, item_features: &ItemFeatures, mirrored_items: &[usize]) -> Vec<usize> {
+    let mut placed_items = vec![];
+    let mut new_mirrored_items = vec![];
+    for (i, pi) in ext_solution.layout.placed_items.drain(..).enumerate() {
+        let mirrored = mirrored_items.contains(&i);
+        let parts = item_features.kit_parts(pi.item_id as usize);
+        if parts.is_empty() {
+            if mirrored {
+                new_mirrored_items.push(placed_items.len());
+            }
+            placed_items.push(pi);
+            continue;
+        }
+        let kit_dt = DTransformation::new(pi.transformation.rotation.to_radians(), pi.transformation.translation);
+        for part in parts {
+            //mirroring the kit (x -> -x) mirrors the offset of the part as well
+            let offset = match mirrored {
+                true => {
+                    let (tx, ty) = part.offset.translation();
+                    DTransformation::new(-part.offset.rotation(), (-tx, ty))
+                }
+                false => part.offset,
+            };
+            let dt = compose_dtransformations(kit_dt, offset);
+            if mirrored {
+                new_mirrored_items.push(placed_items.len());
+            }
+            let mut part_pi = pi.clone();
+            part_pi.item_id = part.item_id as u64;
+            part_pi.transformation.rotation = dt.rotation().to_degrees();
+            part_pi.transformation.translation = dt.translation();
+            placed_items.push(part_pi);
+        }
+    }
+    ext_solution.layout.placed_items = placed_items;
+    new_mirrored_items
+}
+
+/// Applies `inner` first, then `outer`
+fn compose_dtransformations(outer: DTransformation, inner: DTransformation) -> DTransformation {
+    let (sin, cos) = outer.rotation().sin_cos();
+    let (ix, iy) = inner.translation();
+    let (ox, oy) = outer.translation();
+    DTransformation::new(
+        outer.rotation() + inner.rotation(),
+        (cos * ix - sin * iy + ox, sin * ix + cos * iy + oy),
+    )
+}
+
+/// Solution of a strip packing problem in its external format, along with the outcome of its verification
+pub struct ExportedSolution {
+    pub solution: ExtSPSolution,
+    pub mirrored_items: Vec<usize>,
+    pub clearance_violations: Vec<ExtClearanceViolation>,
+    pub proximity_violations: Vec<ExtProximityViolation>,
+    pub orientation_cost: Option<f32>,
+}
+
+impl ExportedSolution {
+    /// Verifies the clearances and proximity groups of the solution and exports it,
+    /// with the kits and mirrored twins expressed in terms of the original items
+    pub fn new(
+        instance: &SPInstance,
+        solution: &SPSolution,
+        item_features: &ItemFeatures,
+        ext_strip_features: &ExtStripFeatures,
+    ) -> Self {
+        let border_margin = ext_strip_features.border_margin.unwrap_or(0.0);
+        let clearance_violations = verify_clearances(instance, &solution.layout_snapshot, item_features, border_margin);
+        let proximity_violations = verify_proximity(instance, solution, item_features);
+        let orientation_cost = report_orientation_cost(solution, item_features);
+        let mut ext_solution = jagua_rs::probs::spp::io::export(instance, solution, *EPOCH);
+        let mirrored_items = export_mirroring(&mut ext_solution, item_features);
+        let mirrored_items = export_kits(&mut ext_solution, item_features, &mirrored_items);
+        Self {
+            solution: ext_solution,
+            mirrored_items,
+            clearance_violations,
+            proximity_violations,
+            orientation_cost,
+        }
+    }
+
+    pub fn into_output(self, instance: ExtSPInstance, strip_features: ExtStripFeatures) -> SPOutput {
+        SPOutput {
+            instance,
+            strip_features,
+            solution: self.solution,
+            container_size: None,
+            mirrored_items: self.mirrored_items,
+            extra_items: vec![],
+            clearance_violations: self.clearance_violations,
+            proximity_violations: self.proximity_violations,
+            orientation_cost: self.orientation_cost,
+            runs: vec![],
+        }
+    }
+}
+
+/// Logs the total cost of the disfavored orientations in the solution, if the items have orientation costs
+fn report_orientation_cost(solution: &SPSolution, item_features: &ItemFeatures) -> Option<f32> {
+    if item_features.orientation_costs.is_empty() {
+        return None;
+    }
+    let cost = solution_orientation_cost(solution, &item_features.orientation_costs);
+    info!("[EXPORT] total orientation cost: {:.3}", cost);
+    Some(cost)
+}
+
+/// Refers the placements of mirrored twins in an exported solution back to their original items.
+/// Returns the indices of the placed items which are mirrored.
+pub fn export_mirroring(ext_solution: &mut ExtSPSolution, item_features: &ItemFeatures) -> Vec<usize> {
+    ext_solution
+        .layout
+        .placed_items
+        .iter_mut()
+        .enumerate()
+        .filter(|(_, pi)| item_features.is_mirrored(pi.item_id as usize))
+        .map(|(i, pi)| {
+            pi.item_id = item_features.original(pi.item_id as usize) as u64;
+            i
+        })
+        .collect()
+}
+
+pub fn read_bpp_instance_json(path: &Path) -> Result<ExtBPInstance> {
+    let file = File::open(path).context("could not open instance file")?;
+    serde_json::from_reader(BufReader::new(file))
+        .context("not a valid bin packing instance (ExtBPInstance)")
+}
+
+pub fn read_roll_catalogue_json(path: &Path) -> Result<ExtRollCatalogue> {
+    let file = File::open(path).context("could not open instance file")?;
+    serde_json::from_reader(BufReader::new(file))
+        .context("not a valid roll catalogue (ExtRollCatalogue)")
+}
+
+pub fn read_marker_order_json(path: &Path) -> Result<ExtMarkerOrder> {
+    let file = File::open(path).context("could not open instance file")?;
+    serde_json::from_reader(BufReader::new(file))
+        .context("not a valid marker order (ExtMarkerOrder)")
+}
+
+pub fn read_kp_instance_json(path: &Path) -> Result<ExtKPInstance> {
+    let file = File::open(path).context("could not open instance file")?;
+    serde_json::from_reader(BufReader::new(file))
+        .context("not a valid knapsack instance (ExtKPInstance)")
+}
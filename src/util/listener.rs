@@ -1,8 +1,11 @@
+use crate::optimizer::problem::{SeparableProblem, StripProblem};
+use jagua_rs::entities::Instance;
 use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use log::info;
 
 /// Trait for listeners that can receive solutions during the optimization process
-pub trait SolutionListener {
-    fn report(&mut self, report: ReportType, solution: &SPSolution, instance: &SPInstance);
+pub trait SolutionListener<P: SeparableProblem = StripProblem> {
+    fn report(&mut self, report: ReportType, solution: &P::Solution, instance: &P::Instance);
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -22,8 +25,28 @@ pub enum ReportType {
 /// A dummy implementation of the `SolutionListener` trait that does nothing.
 pub struct DummySolListener;
 
-impl SolutionListener for DummySolListener {
-    fn report(&mut self, _report: ReportType, _solution: &SPSolution, _instance: &SPInstance) {
+impl<P: SeparableProblem> SolutionListener<P> for DummySolListener {
+    fn report(&mut self, _report: ReportType, _solution: &P::Solution, _instance: &P::Instance) {
         // Do nothing
     }
 }
+
+/// Logs the progress of one of multiple runs executing concurrently.
+pub struct ProgressListener {
+    pub run: usize,
+}
+
+impl SolutionListener for ProgressListener {
+    fn report(&mut self, report: ReportType, solution: &SPSolution, instance: &SPInstance) {
+        match report {
+            ReportType::ExplFeas | ReportType::CmprFeas | ReportType::Final => info!(
+                "[RUN] [id:{:>3}] {:?}: width: {:.3} ({:.3}%)",
+                self.run,
+                report,
+                solution.strip_width(),
+                solution.density(instance) * 100.0
+            ),
+            ReportType::ExplInfeas | ReportType::ExplImproving => {}
+        }
+    }
+}
@@ -2,6 +2,8 @@ pub mod assertions;
 
 pub mod io;
 pub mod bit_reversal_iterator;
+pub mod clearance;
+pub mod proximity;
 pub mod listener;
 pub mod svg_exporter;
 pub mod terminator;
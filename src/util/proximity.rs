@@ -0,0 +1,66 @@
+use crate::consts::CLEARANCE_VERIFICATION_TOLERANCE;
+use crate::optimizer::problem::ItemFeatures;
+use crate::quantify::proximity::x_distance;
+use crate::util::io::ExtProximityViolation;
+use itertools::Itertools;
+use jagua_rs::entities::Instance;
+use jagua_rs::geometry::geo_traits::Transformable;
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use log::{info, warn};
+
+/// Verifies the proximity groups of a solution on the original (non-inflated) shapes of the items:
+/// the x-distance between two items of the same group should not exceed the maximum distance of the group.
+/// Logs and returns the violated groups, with the largest x-distance between two of their items.
+pub fn verify_proximity(
+    instance: &SPInstance,
+    solution: &SPSolution,
+    item_features: &ItemFeatures,
+) -> Vec<ExtProximityViolation> {
+    let groups = &item_features.proximity;
+    if groups.is_empty() {
+        return vec![];
+    }
+    let placed = solution
+        .layout_snapshot
+        .placed_items
+        .values()
+        .filter_map(|pi| {
+            let group = groups.group(pi.item_id)?;
+            let orig = &instance.item(pi.item_id).shape_orig;
+            let shape = orig
+                .shape
+                .transform_clone(&orig.pre_transform.compose())
+                .transform_clone(&pi.d_transf.compose());
+            Some((group, shape))
+        })
+        .into_group_map();
+
+    let violations = placed
+        .into_iter()
+        .sorted_by_key(|(group, _)| *group)
+        .filter_map(|(group, shapes)| {
+            let max_distance = groups.max_dists[group];
+            let distance = shapes
+                .iter()
+                .tuple_combinations()
+                .map(|(s1, s2)| x_distance(s1, s2))
+                .fold(0.0, f32::max);
+            match distance > max_distance * (1.0 + CLEARANCE_VERIFICATION_TOLERANCE) {
+                true => {
+                    warn!("[PROX] items of group {} are {:.3} apart (max: {:.3})", group, distance, max_distance);
+                    Some(ExtProximityViolation {
+                        group,
+                        distance,
+                        max_distance,
+                    })
+                }
+                false => None,
+            }
+        })
+        .collect_vec();
+
+    if violations.is_empty() {
+        info!("[PROX] all proximity groups are respected");
+    }
+    violations
+}
@@ -1,7 +1,11 @@
 use std::path::Path;
+use itertools::Itertools;
+use jagua_rs::entities::LayoutSnapshot;
 use jagua_rs::io::svg::s_layout_to_svg;
 use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
 use log::Level;
+use svg::Document;
+use svg::node::element::{Polygon, Title};
 use crate::consts::DRAW_OPTIONS;
 use crate::util::io;
 use crate::util::listener::{SolutionListener, ReportType};
@@ -49,10 +53,12 @@ impl SolutionListener for SvgExporter{
         let file_name = format!("{}_{:.3}_{}", self.svg_counter, solution.strip_width(), suffix);
         if let Some(live_path) = &self.live_path {
             let svg = s_layout_to_svg(&solution.layout_snapshot, instance, DRAW_OPTIONS, &file_name.as_str());
+            let svg = draw_quality_zones(svg, &solution.layout_snapshot);
             io::write_svg(&svg, Path::new(live_path), Level::Trace).expect("failed to write live svg");
         }
         if let Some(intermediate_dir) = &self.intermediate_dir && report_type != ReportType::ExplImproving {
             let svg = s_layout_to_svg(&solution.layout_snapshot, instance, DRAW_OPTIONS, file_name.as_str());
+            let svg = draw_quality_zones(svg, &solution.layout_snapshot);
             let file_path = &*format!("{intermediate_dir}/{file_name}.svg");
             io::write_svg(&svg, Path::new(file_path), Level::Trace).expect("failed to write intermediate svg");
             self.svg_counter += 1;
@@ -60,7 +66,29 @@ impl SolutionListener for SvgExporter{
         if let Some(final_path) = &self.final_path && report_type == ReportType::Final {
             let stem = Path::new(final_path).file_stem().unwrap();
             let svg = s_layout_to_svg(&solution.layout_snapshot, instance, DRAW_OPTIONS, stem.to_str().unwrap());
+            let svg = draw_quality_zones(svg, &solution.layout_snapshot);
             io::write_svg(&svg, Path::new(final_path), Level::Info).expect("failed to write final svg");
         }
     }
-}
\ No newline at end of file
+}
+
+/// Overlays the quality zones of the container on the document, lower quality zones are drawn more opaque.
+pub fn draw_quality_zones(mut document: Document, layout_snapshot: &LayoutSnapshot) -> Document {
+    for zone in layout_snapshot.container.quality_zones.iter().flatten() {
+        let opacity = 0.5 / (zone.quality + 1) as f32;
+        for shape in zone.shapes.iter() {
+            let points = shape.vertices.iter()
+                .map(|p| format!("{},{}", p.0, p.1))
+                .join(" ");
+            let polygon = Polygon::new()
+                .set("points", points)
+                .set("fill", "#FF8C00")
+                .set("fill-opacity", opacity)
+                .set("stroke", "#FF8C00")
+                .set("stroke-width", 0.001 * layout_snapshot.container.outer_cd.diameter)
+                .add(Title::new(format!("quality zone: {}", zone.quality)));
+            document = document.add(polygon);
+        }
+    }
+    document
+}
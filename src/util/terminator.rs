@@ -36,3 +36,36 @@ impl Terminator for BasicTerminator {
         self.timeout
     }
 }
+
+/// Terminator with a timeout of its own, which also terminates whenever its parent does.
+/// Setting its timeout leaves the parent untouched, e.g. to split a phase into shorter runs.
+#[derive(Debug, Clone)]
+pub struct ChildTerminator<'a, T: Terminator> {
+    pub parent: &'a T,
+    pub timeout: Option<Instant>,
+}
+
+impl<'a, T: Terminator> ChildTerminator<'a, T> {
+    pub fn new(parent: &'a T) -> Self {
+        Self { parent, timeout: None }
+    }
+}
+
+impl<T: Terminator> Terminator for ChildTerminator<'_, T> {
+    fn kill(&self) -> bool {
+        self.parent.kill()
+            || self.timeout
+                .map_or(false, |timeout| Instant::now() > timeout)
+    }
+
+    fn new_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(Instant::now() + timeout);
+    }
+
+    fn timeout_at(&self) -> Option<Instant> {
+        match (self.timeout, self.parent.timeout_at()) {
+            (Some(own), Some(parent)) => Some(own.min(parent)),
+            (own, parent) => own.or(parent),
+        }
+    }
+}
@@ -2,7 +2,6 @@
 mod integration_tests {
     use anyhow::Result;
     use jagua_rs::io::import::Importer;
-    use rand::prelude::SmallRng;
     use rand::SeedableRng;
     use sparrow::config::DEFAULT_SPARROW_CONFIG;
     use sparrow::consts::LBF_SAMPLE_CONFIG;
@@ -17,6 +16,32 @@ mod integration_tests {
     use std::time::Duration;
     use test_case::test_case;
     use sparrow::util::listener::DummySolListener;
+    use sparrow::optimizer::incremental::{reoptimize, DemandDelta};
+    use sparrow::optimizer::problem::{ItemFeatures, StripFeatures};
+    use sparrow::quantify::tracker::CollisionTracker;
+    use jagua_rs::probs::spp::entities::SPProblem;
+    use rand_chacha::ChaCha20Rng;
+    use sparrow::sample::rot_intervals::RotIntervals;
+    use sparrow::optimizer::marker::{plan_markers, SizeOrder};
+    use sparrow::optimizer::problem::KitPart;
+    use sparrow::util::io::{ExtItemAttributes, ExtItemFeatures, ExtSize};
+    use jagua_rs::geometry::DTransformation;
+    use jagua_rs::io::ext_repr::ExtShape;
+    use sparrow::EPOCH;
+    use std::f32::consts::PI;
+    use sparrow::optimizer::bpp::lbf::BPLBFBuilder;
+    use sparrow::optimizer::bpp::eliminate::elimination_phase;
+    use sparrow::optimizer::bpp::downsize::downsize_phase;
+    use sparrow::optimizer::bpp::bin_separator;
+    use jagua_rs::probs::bpp::entities::{BPProblem, BPSolution};
+    use sparrow::config::IslandConfig;
+    use sparrow::optimizer::islands::{island_configs, island_exploration_phase};
+    use std::time::Instant;
+    use sparrow::optimizer::modes::solve_strip_packing;
+    use sparrow::util::io::ExtStripFeatures;
+    use sparrow::quantify::nesting::fits_in_holes;
+    use jagua_rs::geometry::primitives::{Point, SPolygon};
+    use sparrow::quantify::quantify_collision_poly_hole;
 
     const EXPLORE_TIMEOUT: Duration = Duration::from_secs(10);
     const COMPRESS_TIMEOUT: Duration = Duration::from_secs(10);
@@ -39,12 +64,12 @@ mod integration_tests {
         let rng = match RNG_SEED {
             Some(seed) => {
                 println!("[TEST] using provided seed: {}", seed);
-                SmallRng::seed_from_u64(seed as u64)
+                ChaCha20Rng::seed_from_u64(seed as u64)
             }
             None => {
                 let seed = rand::random();
                 println!("[TEST] no seed provided, using: {}", seed);
-                SmallRng::seed_from_u64(seed)
+                ChaCha20Rng::seed_from_u64(seed)
             }
         };
 
@@ -62,4 +87,410 @@ mod integration_tests {
         compression_phase(&instance, &mut separator, final_explore_sol, &mut sol_listener, &terminator, &config.cmpr_cfg);
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn reoptimize_after_demand_change() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.time_limit = Duration::from_secs(5);
+        config.cmpr_cfg.time_limit = Duration::from_secs(2);
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/swim.json");
+        let json_instance = io::read_spp_instance_json(Path::new(&input_file_path))?;
+
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import(&importer, &json_instance)?;
+
+        //warm start from a constructed solution
+        let mut builder = LBFBuilder::new(instance.clone(), ChaCha20Rng::seed_from_u64(0), LBF_SAMPLE_CONFIG).construct();
+        let solution = builder.prob.save();
+
+        let delta = DemandDelta {
+            added: vec![(0, 1)],
+            removed: vec![(1, 1)],
+        };
+        let item_features = ItemFeatures::default();
+        let mut terminator = BasicTerminator::new();
+        let (new_instance, new_solution) = reoptimize(
+            &instance,
+            &solution,
+            &delta,
+            StripFeatures::default(),
+            item_features.clone(),
+            ChaCha20Rng::seed_from_u64(1),
+            &mut DummySolListener,
+            &mut terminator,
+            &config.expl_cfg,
+            &config.cmpr_cfg,
+        )?;
+        assert_eq!(new_instance.items[0].1, instance.items[0].1 + 1);
+        assert_eq!(new_instance.items[1].1, instance.items[1].1 - 1);
+
+        //all copies of the new demand are placed without any collisions
+        let mut prob = SPProblem::new(new_instance.clone());
+        prob.restore(&new_solution);
+        assert!(prob.item_demand_qtys.iter().all(|qty| *qty == 0));
+        let ct = CollisionTracker::new(&prob.layout, item_features.holes.clone(), item_features.regions.clone());
+        assert_eq!(ct.get_total_loss(), 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn demand_delta_rejects_excess_removal() -> Result<()> {
+        let config = DEFAULT_SPARROW_CONFIG;
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/swim.json");
+        let json_instance = io::read_spp_instance_json(Path::new(&input_file_path))?;
+
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import(&importer, &json_instance)?;
+
+        let delta = DemandDelta {
+            added: vec![],
+            removed: vec![(0, instance.items[0].1 + 1)],
+        };
+        assert!(delta.apply(&instance, &ItemFeatures::default()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn rotation_intervals() {
+        let intervals = RotIntervals::new(vec![(0.0, 0.5), (PI, PI + 0.5)]);
+        assert!(intervals.contains(0.25));
+        assert!(intervals.contains(0.25 + 2.0 * PI));
+        assert!(!intervals.contains(1.0));
+
+        assert_eq!(intervals.closest(0.25), 0.25);
+        assert!((intervals.closest(0.6) - 0.5).abs() < 1e-5);
+        assert!((intervals.closest(-0.1) - 0.0).abs() < 1e-5);
+
+        //both intervals are equally long, so both get two rotations: their bounds
+        let spread = intervals.spread(4);
+        let expected = [0.0, 0.5, PI, PI + 0.5];
+        assert_eq!(spread.len(), expected.len());
+        assert!(spread.iter().zip(expected).all(|(r, e)| (r - e).abs() < 1e-5));
+        assert_eq!(intervals.spread(1).len(), 2);
+    }
+
+    #[test]
+    fn fold_instance_halves_strip_and_demand() -> Result<()> {
+        let ext_instance = io::read_spp_instance_json(Path::new(&format!("{INSTANCE_BASE_PATH}/swim.json")))?;
+        let (folded_instance, folded_features) = io::fold_instance(&ext_instance, &ExtItemFeatures::default());
+
+        assert_eq!(folded_instance.strip_height, ext_instance.strip_height / 2.0);
+        for (folded, orig) in folded_instance.items.iter().zip(ext_instance.items.iter()) {
+            assert_eq!(folded.demand, orig.demand.div_ceil(2));
+        }
+        //every item is allowed to be mirrored onto the other half
+        for item in ext_instance.items.iter() {
+            assert!(folded_features.items.iter().any(|attr| attr.id == item.base.id && attr.allow_mirror));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn marker_instance_demand_follows_ratios() -> Result<()> {
+        let ext_instance = io::read_spp_instance_json(Path::new(&format!("{INSTANCE_BASE_PATH}/swim.json")))?;
+        let sizes = vec![
+            ExtSize { name: "S".to_string(), item_ids: vec![0, 1], order_qty: 10 },
+            ExtSize { name: "M".to_string(), item_ids: vec![2], order_qty: 5 },
+        ];
+        let (marker_instance, _) = io::marker_instance(&ext_instance, &ExtItemFeatures::default(), &sizes, &[2, 1])?;
+
+        assert_eq!(marker_instance.items[0].demand, 2 * ext_instance.items[0].demand);
+        assert_eq!(marker_instance.items[1].demand, 2 * ext_instance.items[1].demand);
+        assert_eq!(marker_instance.items[2].demand, ext_instance.items[2].demand);
+        //items without a size are not part of the marker
+        assert!(marker_instance.items[3..].iter().all(|item| item.demand == 0));
+
+        let overlapping_sizes = vec![
+            ExtSize { name: "S".to_string(), item_ids: vec![0], order_qty: 10 },
+            ExtSize { name: "M".to_string(), item_ids: vec![0], order_qty: 5 },
+        ];
+        assert!(io::marker_instance(&ext_instance, &ExtItemFeatures::default(), &overlapping_sizes, &[1, 1]).is_err());
+        Ok(())
+    }
+
+    #[test_case(4, None; "unlimited garments")]
+    #[test_case(4, Some(2); "at most two garments")]
+    #[test_case(1, None; "single ply")]
+    fn planned_markers_cover_order(max_plies: usize, max_garments: Option<usize>) {
+        let sizes = vec![
+            SizeOrder { name: "S".to_string(), order_qty: 10 },
+            SizeOrder { name: "M".to_string(), order_qty: 7 },
+            SizeOrder { name: "L".to_string(), order_qty: 0 },
+        ];
+        let markers = plan_markers(&sizes, max_plies, max_garments);
+
+        for (s, size) in sizes.iter().enumerate() {
+            let n_cut = markers.iter().map(|m| m.ratios[s] * m.plies).sum::<usize>();
+            assert_eq!(n_cut, size.order_qty);
+        }
+        for marker in markers.iter() {
+            assert!(marker.plies >= 1 && marker.plies <= max_plies);
+            assert!(marker.n_garments() <= max_garments.unwrap_or(usize::MAX));
+        }
+    }
+
+    #[test]
+    fn mirrored_twins_are_appended() -> Result<()> {
+        let ext_instance = io::read_spp_instance_json(Path::new(&format!("{INSTANCE_BASE_PATH}/swim.json")))?;
+        let ext_features = ExtItemFeatures {
+            items: vec![ExtItemAttributes { id: 0, allow_mirror: true, ..ExtItemAttributes::default() }],
+            ..ExtItemFeatures::default()
+        };
+        let n_items = ext_instance.items.len();
+        let (twinned_instance, item_features) = io::add_mirrored_twins(&ext_instance, &ext_features);
+
+        assert_eq!(twinned_instance.items.len(), n_items + 1);
+        assert_eq!(item_features.mirror_twin(0), Some(n_items));
+        assert!(item_features.is_mirrored(n_items));
+        assert_eq!(item_features.original(n_items), 0);
+        assert_eq!(twinned_instance.items[n_items].demand, ext_instance.items[0].demand);
+
+        //the twin is mirrored around the y-axis, with its vertices reversed to keep their winding order
+        match (&ext_instance.items[0].base.shape, &twinned_instance.items[n_items].base.shape) {
+            (ExtShape::SimplePolygon(orig), ExtShape::SimplePolygon(twin)) => {
+                let mirrored = orig.0.iter().rev().map(|&(x, y)| (-x, y)).collect::<Vec<_>>();
+                assert_eq!(twin.0, mirrored);
+            }
+            _ => panic!("swim items are simple polygons"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn kits_are_exported_as_parts() -> Result<()> {
+        let config = DEFAULT_SPARROW_CONFIG;
+        let json_instance = io::read_spp_instance_json(Path::new(&format!("{INSTANCE_BASE_PATH}/swim.json")))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import(&importer, &json_instance)?;
+
+        let mut builder = LBFBuilder::new(instance.clone(), ChaCha20Rng::seed_from_u64(0), LBF_SAMPLE_CONFIG).construct();
+        let solution = builder.prob.save();
+        let ext_solution = jagua_rs::probs::spp::io::export(&instance, &solution, *EPOCH);
+
+        //pretend item 0 is a kit of two parts
+        let offset = (10.0, 0.0);
+        let item_features = ItemFeatures {
+            kits: vec![vec![
+                KitPart { item_id: 1, offset: DTransformation::empty() },
+                KitPart { item_id: 2, offset: DTransformation::new(PI / 2.0, offset) },
+            ]],
+            ..ItemFeatures::default()
+        };
+        let kit_idx = ext_solution.layout.placed_items.iter().position(|pi| pi.item_id == 0).unwrap();
+        let kit_pi = ext_solution.layout.placed_items[kit_idx].clone();
+        let n_placed = ext_solution.layout.placed_items.len();
+        let n_kits = ext_solution.layout.placed_items.iter().filter(|pi| pi.item_id == 0).count();
+
+        for mirrored in [false, true] {
+            let mut exported = ext_solution.clone();
+            let mirrored_items = match mirrored {
+                true => vec![kit_idx],
+                false => vec![],
+            };
+            let new_mirrored_items = io::export_kits(&mut exported, &item_features, &mirrored_items);
+            assert_eq!(exported.layout.placed_items.len(), n_placed + n_kits);
+            assert!(exported.layout.placed_items.iter().all(|pi| pi.item_id != 0));
+
+            //the parts of the kit follow each other, the first one at the position of the kit
+            let (p1, p2) = (&exported.layout.placed_items[kit_idx], &exported.layout.placed_items[kit_idx + 1]);
+            assert_eq!((p1.item_id, p2.item_id), (1, 2));
+            assert_eq!(p1.transformation.translation, kit_pi.transformation.translation);
+
+            //the offset of the second part is rotated along with the kit (and mirrored along with it)
+            let (sign, part_rotation) = match mirrored {
+                true => (-1.0, -90.0),
+                false => (1.0, 90.0),
+            };
+            let (sin, cos) = kit_pi.transformation.rotation.to_radians().sin_cos();
+            let (ox, oy) = (sign * offset.0, offset.1);
+            let (kx, ky) = kit_pi.transformation.translation;
+            let (px, py) = p2.transformation.translation;
+            assert!((px - (kx + cos * ox - sin * oy)).abs() < 1e-3);
+            assert!((py - (ky + sin * ox + cos * oy)).abs() < 1e-3);
+            assert!((p2.transformation.rotation - (kit_pi.transformation.rotation + part_rotation)).abs() < 1e-3);
+
+            match mirrored {
+                true => assert_eq!(new_mirrored_items, vec![kit_idx, kit_idx + 1]),
+                false => assert!(new_mirrored_items.is_empty()),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn bin_packing_phases_keep_items_placed() -> Result<()> {
+        let config = DEFAULT_SPARROW_CONFIG;
+        //bin packing instance with the items of swim and two sheet types, the smaller one cheaper per area
+        let json_instance = io::read_spp_instance_json(Path::new(&format!("{INSTANCE_BASE_PATH}/swim.json")))?;
+        let sheet = |id: u64, width: f32, cost: u64| {
+            serde_json::json!({
+                "id": id,
+                "shape": {"type": "rectangle", "data": {"x_min": 0.0, "y_min": 0.0, "width": width, "height": json_instance.strip_height}},
+                "stock": 10,
+                "cost": cost,
+            })
+        };
+        let bpp_json = serde_json::json!({
+            "name": "swim_bpp",
+            "items": serde_json::to_value(&json_instance.items)?,
+            "bins": [sheet(0, json_instance.strip_height, 4), sheet(1, json_instance.strip_height / 2.0, 1)],
+        });
+        let bpp_path = std::env::temp_dir().join("swim_bpp.json");
+        std::fs::write(&bpp_path, serde_json::to_string(&bpp_json)?)?;
+        let ext_instance = io::read_bpp_instance_json(&bpp_path)?;
+
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::bpp::io::import(&importer, &ext_instance)?;
+
+        //every copy of every item is placed on one of the sheets, without any collisions
+        let assert_feasible = |prob: &mut BPProblem, solution: &BPSolution| {
+            prob.restore(solution);
+            assert!(prob.item_demand_qtys.iter().all(|qty| *qty == 0));
+            let item_features = ItemFeatures::default();
+            for layout in prob.layouts.values() {
+                let ct = CollisionTracker::new(layout, item_features.holes.clone(), item_features.regions.clone());
+                assert_eq!(ct.get_total_loss(), 0.0);
+            }
+        };
+
+        let mut builder = BPLBFBuilder::new(instance.clone(), ChaCha20Rng::seed_from_u64(0), LBF_SAMPLE_CONFIG).construct()?;
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let mut terminator = BasicTerminator::new();
+
+        terminator.new_timeout(Duration::from_secs(5));
+        let mut expl_sep = bin_separator(&builder.prob, ChaCha20Rng::seed_from_u64(2), config.expl_cfg.separator_config);
+        let expl_sol = elimination_phase(&instance, &mut builder.prob, &mut expl_sep, &mut rng, &terminator, &config.expl_cfg);
+        assert_feasible(&mut builder.prob, &expl_sol);
+
+        terminator.new_timeout(Duration::from_secs(5));
+        let mut cmpr_sep = bin_separator(&builder.prob, ChaCha20Rng::seed_from_u64(3), config.cmpr_cfg.separator_config);
+        let cmpr_sol = downsize_phase(&instance, &mut builder.prob, &mut cmpr_sep, &mut rng, &terminator);
+        assert_feasible(&mut builder.prob, &cmpr_sol);
+        Ok(())
+    }
+
+    #[test]
+    fn island_exploration_runs_every_epoch() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.max_conseq_failed_attempts = None;
+        let json_instance = io::read_spp_instance_json(Path::new(&format!("{INSTANCE_BASE_PATH}/swim.json")))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import(&importer, &json_instance)?;
+
+        let builder = LBFBuilder::new(instance.clone(), ChaCha20Rng::seed_from_u64(0), LBF_SAMPLE_CONFIG).construct();
+        let mut separator = Separator::new(builder.instance, builder.prob, builder.rng, config.expl_cfg.separator_config);
+
+        let phase_duration = Duration::from_secs(6);
+        let island_config = IslandConfig { n_islands: 2, n_epochs: 3 };
+        let mut terminator = BasicTerminator::new();
+        terminator.new_timeout(phase_duration);
+        let phase_end = terminator.timeout_at();
+
+        let start = Instant::now();
+        let solutions = island_exploration_phase(
+            &instance,
+            &mut separator,
+            &mut DummySolListener,
+            &terminator,
+            &island_configs(&config.expl_cfg, island_config.n_islands),
+            &island_config,
+        );
+        //the epochs do not touch the timeout of the phase, and together they last the whole phase instead of a single epoch
+        assert_eq!(terminator.timeout_at(), phase_end);
+        assert!(start.elapsed() > phase_duration * 2 / island_config.n_epochs as u32);
+        assert!(!solutions.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn placing_and_removing_items_keeps_weights() -> Result<()> {
+        let config = DEFAULT_SPARROW_CONFIG;
+        let json_instance = io::read_spp_instance_json(Path::new(&format!("{INSTANCE_BASE_PATH}/swim.json")))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import(&importer, &json_instance)?;
+
+        let builder = LBFBuilder::new(instance.clone(), ChaCha20Rng::seed_from_u64(0), LBF_SAMPLE_CONFIG).construct();
+        let mut separator = Separator::new(builder.instance, builder.prob, builder.rng, config.expl_cfg.separator_config);
+
+        //place a copy on top of an existing item and let the weight of the collision grow
+        let (pk, item_id, d_transf) = separator.prob.layout.placed_items.iter().next().map(|(pk, pi)| (pk, pi.item_id, pi.d_transf)).unwrap();
+        let copy_pk = separator.place_item(item_id, d_transf);
+        separator.ct.increment_weights();
+        let weight = separator.ct.get_pair_weight(pk, copy_pk);
+        assert!(weight > 1.0);
+
+        //remove another item, the tracker should match a rebuilt one, but keep the weight of the collision
+        let other_pk = separator.prob.layout.placed_items.keys().find(|k| *k != pk && *k != copy_pk).unwrap();
+        separator.remove_item(other_pk);
+        let rebuilt = CollisionTracker::new(&separator.prob.layout, separator.prob.item_features.holes.clone(), separator.prob.item_features.regions.clone());
+        for pk in separator.prob.layout.placed_items.keys() {
+            assert!((separator.ct.get_loss(pk) - rebuilt.get_loss(pk)).abs() < 1e-3);
+        }
+        assert_eq!(separator.ct.get_pair_weight(pk, copy_pk), weight);
+        Ok(())
+    }
+
+    #[test]
+    fn folded_strip_packing_covers_demand() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.time_limit = Duration::from_secs(2);
+        config.cmpr_cfg.time_limit = Duration::from_secs(1);
+        let ext_instance = io::read_spp_instance_json(Path::new(&format!("{INSTANCE_BASE_PATH}/swim.json")))?;
+
+        let output = solve_strip_packing(
+            ext_instance.clone(),
+            ExtItemFeatures::default(),
+            ExtStripFeatures::default(),
+            None,
+            true,
+            1,
+            &config,
+            ChaCha20Rng::seed_from_u64(0),
+            &mut DummySolListener,
+            &mut BasicTerminator::new(),
+        )?;
+
+        //both halves of the strip are exported, every item is placed at least as often as demanded
+        for item in ext_instance.items.iter() {
+            let n_placed = output.solution.layout.placed_items.iter().filter(|pi| pi.item_id == item.base.id).count();
+            assert!(n_placed as u64 >= item.demand);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn notched_hole_does_not_nest_crossed_shape() -> Result<()> {
+        let polygon = |points: &[(f32, f32)]| SPolygon::new(points.iter().map(|&(x, y)| Point(x, y)).collect());
+        //square hole with a thin notch reaching down from the top edge
+        let hole = polygon(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (5.1, 10.0), (5.1, 1.0), (4.9, 1.0), (4.9, 10.0), (0.0, 10.0)])?;
+        let holes = [hole];
+
+        //the notch crosses the shape, without any vertex of either one inside the other
+        let crossed = polygon(&[(2.0, 2.0), (8.0, 2.0), (8.0, 4.0), (2.0, 4.0)])?;
+        assert!(!fits_in_holes(&crossed, &holes, DTransformation::empty()));
+
+        let beside = polygon(&[(1.0, 1.0), (4.0, 1.0), (4.0, 9.0), (1.0, 9.0)])?;
+        assert!(fits_in_holes(&beside, &holes, DTransformation::empty()));
+        Ok(())
+    }
+
+    #[test]
+    fn diagonal_defect_loss_follows_overlap() -> Result<()> {
+        let polygon = |points: &[(f32, f32)]| SPolygon::new(points.iter().map(|&(x, y)| Point(x, y)).collect());
+        let square = |x: f32, y: f32| -> Result<SPolygon> {
+            let mut s = polygon(&[(x, y), (x + 2.0, y), (x + 2.0, y + 2.0), (x, y + 2.0)])?;
+            s.generate_surrogate(DEFAULT_SPARROW_CONFIG.cde_config.item_surrogate_config)?;
+            Ok(s)
+        };
+        //thin defect along the diagonal of a 20x20 square
+        let defect = polygon(&[(0.0, 0.0), (0.5, 0.0), (20.0, 19.5), (20.0, 20.0), (19.5, 20.0), (0.0, 0.5)])?;
+
+        //in the corner of the bounding box of the defect, far away from the defect itself
+        let corner = quantify_collision_poly_hole(&square(16.0, 1.0)?, &defect);
+        //on top of the defect
+        let crossing = quantify_collision_poly_hole(&square(9.0, 9.0)?, &defect);
+        assert!(corner > 0.0);
+        assert!(corner < 0.5 * crossing, "corner: {corner}, crossing: {crossing}");
+        Ok(())
+    }
+}